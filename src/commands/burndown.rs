@@ -0,0 +1,91 @@
+use chrono::Local;
+use mythos_core::{printerror, printinfo};
+
+use crate::requirements::{history_path, read_history, Burndown, BurndownGroup, Filter, Requirement};
+use crate::Format;
+
+/// ody burndown [options] requirements_file [spreadsheet]
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut positionals: Vec<String> = Vec::new();
+    let mut format = Format::Text;
+    let mut group_by = BurndownGroup::Project;
+    let mut filter: Option<Filter> = None;
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-" || !arg.starts_with("-") {
+            positionals.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = match args.next().unwrap_or("".to_string()).as_str() {
+                    "text" => Format::Text,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => {
+                        printerror!("-f/--format must be accompanied with one of: text, csv, json.");
+                        return Err(());
+                    }
+                };
+            },
+            "--by" => {
+                group_by = match args.next().unwrap_or("".to_string()).as_str() {
+                    "project" => BurndownGroup::Project,
+                    "category" => BurndownGroup::Category,
+                    "objective" => BurndownGroup::Objective,
+                    _ => {
+                        printerror!("--by must be accompanied with one of: project, category, objective.");
+                        return Err(());
+                    }
+                };
+            },
+            "--where" => filter = Some(Filter::parse(&args.next().unwrap_or("".to_string())).ok_or(())?),
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+    if positionals.is_empty() {
+        print_help();
+        return Ok(());
+    }
+
+    let (input_path, spreadsheet_path, _) = super::split_files(positionals, 0)?;
+    let (list, sheet) = super::open_files(&input_path, spreadsheet_path, be_verbose)?;
+    let path = match (&sheet, list.is_spreadsheet()) {
+        (Some(sheet), _) => history_path(&sheet.path),
+        (None, true) => history_path(&list.path),
+        (None, false) => {
+            printerror!("History is kept next to the spreadsheet, but none was found for {input_path:?}. Pass one after the requirements file.");
+            return Err(());
+        }
+    };
+    printinfo!(be_verbose, "Reading {path:?}.");
+
+    let reqs: Vec<Requirement> = list.requirements.into_iter().filter(|x| filter.as_ref().is_none_or(|f| f.matches(x))).collect();
+    let history = read_history(&path).ok_or(())?;
+    let burndown = Burndown::new(&reqs, &history, group_by, Local::now().date_naive());
+    if burndown.days.is_empty() && matches!(format, Format::Text) {
+        println!("No status changes recorded.");
+        return Ok(());
+    }
+    let output = match format {
+        Format::Csv => Some(burndown.to_csv_format()),
+        Format::Json => burndown.to_json_format(),
+        _ => Some(burndown.to_chart_format()),
+    };
+    print!("{}", output.ok_or(())?);
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Shows how many requirements were open and done on each day, from the history kept next to the spreadsheet.");
+    println!("ody burndown [options] requirements_file [spreadsheet]");
+    println!("\nThe text output is a chart per group, with the average velocity of the last two weeks and when the open requirements would be done at that rate. If no spreadsheet is given, ./<dir-name>.csv is used if it exists.");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-f | --format fmt\tOutput format: text (default), csv or json.\n--by group\t\tCount per project (default), category or objective.\n--where filter\t\tOnly include requirements matching $filter. See ody query --help.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,84 @@
+use std::{fs, io::Write, path::PathBuf};
+
+use mythos_core::{printerror, printinfo};
+
+use crate::open_writer;
+use crate::project::{changelog_sections, check_objectives, prepend_changelog, Project};
+use crate::requirements::parse_requirement_set;
+
+/// ody changelog [options]
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut project_name: Option<String> = None;
+    let mut spreadsheet_path: Option<PathBuf> = None;
+    let mut list_path: Option<PathBuf> = None;
+    let mut changelog_path: Option<PathBuf> = None;
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--project" => project_name = args.next(),
+            "--sheet" => spreadsheet_path = args.next().map(PathBuf::from),
+            "--list" => list_path = args.next().map(PathBuf::from),
+            "--prepend" => changelog_path = args.next().map(PathBuf::from),
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+
+    let project = Project::find(project_name.as_deref()).ok_or(())?;
+    let spreadsheet_path = spreadsheet_path.unwrap_or(project.spreadsheet_path());
+    printinfo!(be_verbose, "Reading {spreadsheet_path:?} for project {}.", project.name);
+    let reqs = parse_requirement_set(&spreadsheet_path, be_verbose).ok_or(())?;
+    if !check_objectives(&reqs.requirements) {
+        return Err(());
+    }
+    // Only the list file has the long category names.
+    let categories = match &list_path {
+        Some(path) => parse_requirement_set(path, be_verbose).ok_or(())?.categories,
+        None => reqs.categories.clone(),
+    };
+    let sections = changelog_sections(&project, &reqs.requirements, &categories);
+
+    let Some(changelog_path) = changelog_path else {
+        let output = sections.into_iter().map(|(_, x)| x).collect::<Vec<String>>().join("\n");
+        let mut writer = open_writer(&PathBuf::from("-")).ok_or(())?;
+        if let Err(err) = writer.write_all(output.as_bytes()).and_then(|_| writer.flush()) {
+            printerror!("Error while writing changelog. {err}");
+            return Err(());
+        }
+        return Ok(());
+    };
+    let changelog = if changelog_path.is_file() {
+        match fs::read_to_string(&changelog_path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                printerror!("Could not read {changelog_path:?}. {err}");
+                return Err(());
+            }
+        }
+    } else {
+        String::new()
+    };
+    let (changelog, count) = prepend_changelog(&changelog, &sections);
+    if count == 0 {
+        printinfo!("{changelog_path:?} is up to date.");
+        return Ok(());
+    }
+    if let Err(err) = fs::write(&changelog_path, changelog) {
+        printerror!("Could not write {changelog_path:?}. {err}");
+        return Err(());
+    }
+    printinfo!("Added {count} version(s) to {changelog_path:?}.");
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Writes a Keep a Changelog section for each completed objective, listing its requirements by category.");
+    println!("ody changelog [options]");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n--project name\t\tProject to use. Defaults to the project whose working directory contains the current directory.\n--sheet path\t\tSpreadsheet to use. Defaults to $working_dir/requirements.csv.\n--list path\t\tList file to take category names from. Otherwise the abbreviations are used.\n--prepend path\t\tAdd new versions to the top of a changelog (e.g. CHANGELOG.md) instead of printing them. Versions already in it are skipped.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,29 @@
+use crate::config::{Config, CONFIG_FILE_NAME};
+
+/// ody config show
+pub fn run(mut args: impl Iterator<Item = String>) -> Result<(), ()> {
+    match (args.next().as_deref(), args.next()) {
+        (Some("show"), None) => (),
+        _ => {
+            print_help();
+            return Ok(());
+        }
+    }
+    let paths = Config::paths();
+    if paths.is_empty() {
+        println!("# No {CONFIG_FILE_NAME} found. Using the defaults.");
+    }
+    for path in &paths {
+        println!("# Read {path:?}");
+    }
+    print!("{}", Config::get().to_annotated_toml());
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Shows the settings read from {CONFIG_FILE_NAME}.");
+    println!("ody config show");
+    println!("\nPrints every setting as toml, followed by the file it came from, or default. The {CONFIG_FILE_NAME} closest to the current directory, up to the root of its git repository, takes precedence over the one in the mythos config directory ($MYTHOS_CONFIG_DIR/odysseus). Cli flags take precedence over both.");
+    println!("\n\nSettings:");
+    println!("spreadsheet\t\tSpreadsheet used when none is given, relative to the {CONFIG_FILE_NAME} it is set in. Defaults to ./<dir-name>.csv.\nformat\t\t\tFormat of the spreadsheet written by a sync: csv (default), markdown, json, yaml or toml. Overridden by -f and -m.\noverwrite\t\tWhether a sync rewrites the requirements file. Overridden by -w and --overwrite.\nindent_width\t\tSpaces per level of indentation in list files. 0 (default) indents with tabs.\nhash\t\t\tHow hashes are generated for new requirements: default or fnv1a (the same category, id and contents always get the same hash).\n[statuses]\t\tStatus labels, as label = \"list mark\", e.g. blocked = \"-\". Used when printing statuses and by ody set-status.");
+}
@@ -0,0 +1,131 @@
+use mythos_core::{printerror, printinfo};
+
+use crate::requirements::{find_requirement, EditableFile, Transaction};
+
+/// ody add [options] requirements_file [spreadsheet] category parent contents
+pub fn run_add(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let (positionals, be_verbose) = parse_args(args, print_add_help).ok_or(())?;
+    let (input_path, spreadsheet_path, rest) = super::split_files(positionals, 3)?;
+    let (mut list, sheet) = super::open_files(&input_path, spreadsheet_path, be_verbose)?;
+
+    let parent = match parse_id(&rest[1]) {
+        Some(parent) => parent,
+        None => {
+            printerror!("Invalid parent id \"{}\". Use 0 to add a top level requirement.", rest[1]);
+            return Err(());
+        }
+    };
+    let hash = list.add(&rest[0], &parent, &rest[2]).ok_or(())?;
+    save(&list, sheet, &[hash.clone()], &[])?;
+
+    let req = list.get(&hash).ok_or(())?;
+    printinfo!("Added {} {} {}(@{}).", req.category, req.id_to_string(), req.contents, req.hash);
+    return Ok(());
+}
+
+/// ody move [options] requirements_file [spreadsheet] target destination
+pub fn run_move(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let (positionals, be_verbose) = parse_args(args, print_move_help).ok_or(())?;
+    let (input_path, spreadsheet_path, rest) = super::split_files(positionals, 2)?;
+    let (mut list, sheet) = super::open_files(&input_path, spreadsheet_path, be_verbose)?;
+
+    let req = find_requirement(&list.requirements, &rest[0]).ok_or(())?.clone();
+    // Destination is [category.]parent, or just a category.
+    let mut parts: Vec<&str> = rest[1].split('.').collect();
+    let category = match parts.first() {
+        Some(x) if x.parse::<usize>().is_err() => parts.remove(0).to_string(),
+        _ => req.category.to_string(),
+    };
+    let parent = match parse_id(&parts.join(".")) {
+        Some(parent) => parent,
+        None => {
+            printerror!("Invalid destination \"{}\". Expected [category.]parent_id, or a category.", rest[1]);
+            return Err(());
+        }
+    };
+    list.move_to(&req.hash, &category, &parent).ok_or(())?;
+    save(&list, sheet, &[], &[])?;
+
+    let moved = list.get(&req.hash).ok_or(())?;
+    printinfo!("Moved {} {} -> {} {}.", req.category, req.id_to_string(), moved.category, moved.id_to_string());
+    return Ok(());
+}
+
+/// ody delete [options] requirements_file [spreadsheet] target
+pub fn run_delete(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let (positionals, be_verbose) = parse_args(args, print_delete_help).ok_or(())?;
+    let (input_path, spreadsheet_path, rest) = super::split_files(positionals, 1)?;
+    let (mut list, sheet) = super::open_files(&input_path, spreadsheet_path, be_verbose)?;
+
+    let req = find_requirement(&list.requirements, &rest[0]).ok_or(())?.clone();
+    let deleted = list.delete(&req.hash).ok_or(())?;
+    save(&list, sheet, &[], &deleted)?;
+
+    printinfo!("Deleted {} {} {} and {} child requirement(s).", req.category, req.id_to_string(), req.contents, deleted.len() - 1);
+    return Ok(());
+}
+
+/// Write the list, and the spreadsheet updated to match it, together.
+fn save(list: &EditableFile, sheet: Option<EditableFile>, added: &[String], deleted: &[String]) -> Result<(), ()> {
+    let mut transaction = Transaction::new();
+    list.save(&mut transaction).ok_or(())?;
+    if let Some(mut sheet) = sheet {
+        sheet.follow(list, added, deleted);
+        sheet.save(&mut transaction).ok_or(())?;
+    }
+    return transaction.commit().ok_or(());
+}
+
+/// Empty or "0" is the top level.
+fn parse_id(value: &str) -> Option<Vec<usize>> {
+    if value.is_empty() || value == "0" {
+        return Some(Vec::new());
+    }
+    return value.split('.').map(|x| x.parse::<usize>().ok().filter(|x| *x > 0)).collect();
+}
+
+fn parse_args(args: impl Iterator<Item = String>, print_help: fn()) -> Option<(Vec<String>, bool)> {
+    let mut positionals: Vec<String> = Vec::new();
+    let mut be_verbose = false;
+    for arg in args {
+        if !arg.starts_with("-") {
+            positionals.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return None;
+            }
+        }
+    }
+    return Some((positionals, be_verbose));
+}
+
+fn print_options() {
+    println!("\nRequirements can be given by hash or id. Ids can be prefixed with their category, e.g. PROJ.1.2.");
+    println!("The list file is rewritten, and the spreadsheet is updated to match. If no spreadsheet is given, ./<dir-name>.csv is updated if it exists.");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-v | --verbose\t\tPrint extra information.");
+}
+
+fn print_add_help() {
+    println!("Adds a requirement as the last child of parent. Use 0 as parent to add a top level requirement.");
+    println!("ody add [options] requirements_file [spreadsheet] category parent contents");
+    println!("E.g. ody add reqs.md PROJ 3 \"Open a browser\"");
+    print_options();
+}
+
+fn print_move_help() {
+    println!("Moves a requirement and its children to become the last child of another requirement, or to the top level of a category.");
+    println!("ody move [options] requirements_file [spreadsheet] hash|id [category.]parent|category");
+    println!("E.g. ody move reqs.md PROJ.3.2 DOC.1");
+    print_options();
+}
+
+fn print_delete_help() {
+    println!("Deletes a requirement and its children.");
+    println!("ody delete [options] requirements_file [spreadsheet] hash|id");
+    print_options();
+}
@@ -0,0 +1,124 @@
+use std::collections::HashMap;
+use std::io::{stdout, IsTerminal};
+use std::path::PathBuf;
+
+use mythos_core::{printerror, printinfo};
+
+use crate::git::{self, Commit};
+use crate::requirements::{diff_requirements, find_requirement, parse_requirement_set, parse_requirement_set_str, EditableFile, Requirement, RequirementDiff};
+use crate::Format;
+
+/// ody git blame|diff [options] args
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut positionals: Vec<String> = Vec::new();
+    let mut format = Format::Text;
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("-") {
+            positionals.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = match args.next().unwrap_or("".to_string()).as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    "yaml" => Format::Yaml,
+                    _ => {
+                        printerror!("-f/--format must be accompanied with one of: text, json, yaml.");
+                        return Err(());
+                    }
+                };
+            },
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+    if positionals.len() < 2 {
+        print_help();
+        return Ok(());
+    }
+
+    let command = positionals.remove(0);
+    let path = PathBuf::from(positionals.remove(0));
+    if !path.is_file() {
+        printerror!("Requirements file {path:?} does not exist.");
+        return Err(());
+    }
+    if !git::is_in_repository(&path) {
+        printerror!("{path:?} is not in a git repository.");
+        return Err(());
+    }
+    return match (command.as_str(), positionals.as_slice()) {
+        ("blame", [] | [_]) => blame(&path, positionals.first(), be_verbose),
+        ("diff", [from] | [from, _]) => diff(&path, from, positionals.get(1), &format, be_verbose),
+        _ => {
+            printerror!("Unexpected arguments for ody git {command}.");
+            print_help();
+            Err(())
+        }
+    };
+}
+
+/**
+ * When each requirement was introduced, and when its line was last changed. Requirements are found in
+ * older commits by their hash if it is written in the file, otherwise by their contents.
+ */
+fn blame(path: &PathBuf, target: Option<&String>, be_verbose: bool) -> Result<(), ()> {
+    let file = EditableFile::open(path, be_verbose).ok_or(())?;
+    let reqs: Vec<&Requirement> = match target {
+        Some(target) => vec![find_requirement(&file.requirements, target).ok_or(())?],
+        None => file.requirements.iter().collect(),
+    };
+    let lines = git::blame(path).ok_or(())?;
+
+    let describe = |commit: Option<&Commit>| match commit {
+        Some(commit) => format!("{} {} {}: {}", commit.short_hash(), commit.date.format("%Y-%m-%d"), commit.author, commit.summary),
+        None => "not committed yet".to_string(),
+    };
+    for req in reqs {
+        let Some(line) = file.line_index(&req.hash).and_then(|x| lines.get(x)) else {
+            printerror!("git blame only works for list files and csv/md spreadsheets, which have a line per requirement.");
+            return Err(());
+        };
+        let search = if line.contents.contains(&req.hash) { &req.hash } else { &req.contents };
+        printinfo!(be_verbose, "Searching history for \"{search}\".");
+        let introduced = git::log(path, Some(search)).ok_or(())?;
+        println!("{} {} {}(@{})", req.category, req.id_to_string(), req.contents, req.hash);
+        println!("    introduced: {}", describe(introduced.first()));
+        println!("    changed:    {}", describe(line.commit.as_ref()));
+    }
+    return Ok(());
+}
+
+/// Requirements added, removed or changed between two revisions. Without $to, the file as it is now.
+fn diff(path: &PathBuf, from: &str, to: Option<&String>, format: &Format, be_verbose: bool) -> Result<(), ()> {
+    let before = parse_requirement_set_str(path, &git::show(path, from).ok_or(())?, be_verbose).ok_or(())?;
+    let after = match to {
+        Some(to) => parse_requirement_set_str(path, &git::show(path, to).ok_or(())?, be_verbose).ok_or(())?,
+        None => parse_requirement_set(path, be_verbose).ok_or(())?,
+    };
+    let before: HashMap<String, Requirement> = before.requirements.into_iter().map(|x| (x.hash.clone(), x)).collect();
+    let diffs = diff_requirements(&before, &after.requirements);
+    let output = match format {
+        Format::Json => RequirementDiff::to_json_format(&diffs),
+        Format::Yaml => RequirementDiff::to_yaml_format(&diffs),
+        _ => Some(RequirementDiff::to_report(&diffs, stdout().is_terminal())),
+    };
+    print!("{}", output.ok_or(())?);
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Shows the git history of a requirements file, per requirement.");
+    println!("ody git blame [options] requirements_file [hash|id]\tShow the commit that introduced each requirement, and the one that last changed its line.");
+    println!("ody git diff [options] requirements_file from [to]\tShow requirements added, removed or changed between two revisions (e.g. commit hashes). Defaults to the file as it is now.");
+    println!("\nblame needs a list file or csv/md spreadsheet. Spreadsheets can be used with diff, except for xlsx/ods.");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-f | --format fmt\tOutput format of diff: text (default), json or yaml.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,63 @@
+use mythos_core::{printerror, printinfo};
+
+use crate::requirements::{find_requirement, history_path, read_history, Requirement};
+
+/// ody history [options] requirements_file [spreadsheet] target
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut positionals: Vec<String> = Vec::new();
+    let mut be_verbose = false;
+
+    for arg in args {
+        if arg == "-" || !arg.starts_with("-") {
+            positionals.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+    if positionals.is_empty() {
+        print_help();
+        return Ok(());
+    }
+
+    let (input_path, spreadsheet_path, rest) = super::split_files(positionals, 1)?;
+    let (list, sheet) = super::open_files(&input_path, spreadsheet_path, be_verbose)?;
+    let path = match (&sheet, list.is_spreadsheet()) {
+        (Some(sheet), _) => history_path(&sheet.path),
+        (None, true) => history_path(&list.path),
+        (None, false) => {
+            printerror!("History is kept next to the spreadsheet, but none was found for {input_path:?}. Pass one after the requirements file.");
+            return Err(());
+        }
+    };
+    printinfo!(be_verbose, "Reading {path:?}.");
+
+    let req = find_requirement(&list.requirements, &rest[0]).ok_or(())?;
+    println!("{} {} {}(@{})", req.category, req.id_to_string(), req.contents, req.hash);
+    let history = read_history(&path).ok_or(())?;
+    let changes: Vec<_> = history.iter().filter(|x| x.hash == req.hash).collect();
+    if changes.is_empty() {
+        println!("No status changes recorded.");
+    }
+    for change in changes {
+        let new = Requirement::label_status(change.new);
+        match change.old {
+            Some(old) => println!("{} {}: {} -> {new}", change.timestamp.format("%Y-%m-%d %H:%M:%S %:z"), change.source, Requirement::label_status(old)),
+            None => println!("{} {}: created as {new}", change.timestamp.format("%Y-%m-%d %H:%M:%S %:z"), change.source),
+        }
+    }
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Shows every recorded status change of a requirement, from the history kept next to its spreadsheet.");
+    println!("ody history [options] requirements_file [spreadsheet] hash|id");
+    println!("\nRequirements can be given by hash or id. Ids can be prefixed with their category, e.g. PROJ.1.2. If no spreadsheet is given, ./<dir-name>.csv is used if it exists.");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,59 @@
+//! Subcommands. Each takes the remaining cli args, after the subcommand name.
+pub mod burndown;
+pub mod changelog;
+pub mod config;
+pub mod edit;
+pub mod git;
+pub mod history;
+pub mod objective;
+pub mod project;
+pub mod query;
+pub mod report;
+pub mod roadmap;
+pub mod stats;
+pub mod status;
+pub mod trace;
+pub mod watch;
+
+use std::path::PathBuf;
+
+use mythos_core::{printerror, printinfo};
+
+use crate::config::Config;
+use crate::default_spreadsheet_path;
+use crate::requirements::EditableFile;
+
+/// Split positional args into the requirements file, an optional spreadsheet and the last count args.
+fn split_files(mut positionals: Vec<String>, count: usize) -> Result<(PathBuf, Option<PathBuf>, Vec<String>), ()> {
+    if positionals.len() != count + 1 && positionals.len() != count + 2 {
+        printerror!("Expected a requirements file, an optional spreadsheet and {count} more argument(s).");
+        return Err(());
+    }
+    let rest = positionals.split_off(positionals.len() - count);
+    let spreadsheet_path = positionals.get(1).map(PathBuf::from);
+    return Ok((PathBuf::from(&positionals[0]), spreadsheet_path, rest));
+}
+
+/// Open a requirements file for editing, along with the spreadsheet that should be kept in sync
+/// with it. If no spreadsheet is given, the default one is used if it exists.
+fn open_files(input_path: &PathBuf, spreadsheet_path: Option<PathBuf>, be_verbose: bool) -> Result<(EditableFile, Option<EditableFile>), ()> {
+    let list = EditableFile::open(input_path, be_verbose).ok_or(())?;
+    let spreadsheet_path = spreadsheet_path.or_else(|| {
+        if list.is_spreadsheet() {
+            return None;
+        }
+        // The one a sync would write by default first.
+        let (format, use_markdown_output) = Config::get().sync_format();
+        [default_spreadsheet_path(input_path, format.as_ref(), use_markdown_output), default_spreadsheet_path(input_path, None, false), default_spreadsheet_path(input_path, None, true)]
+            .into_iter()
+            .find(|x| x.is_file() && x != input_path)
+    });
+    let sheet = match &spreadsheet_path {
+        Some(path) => {
+            printinfo!(be_verbose, "Updating spreadsheet {path:?}.");
+            Some(EditableFile::open(path, be_verbose).ok_or(())?)
+        },
+        None => None,
+    };
+    return Ok((list, sheet));
+}
@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+
+use chrono::{Local, NaiveDate};
+use mythos_core::{printerror, printinfo};
+
+use crate::project::{assign_objective, check_objectives, objective_version, Objective, Project, Version};
+use crate::requirements::{EditableFile, Transaction};
+
+/// ody objective [options] list|add|remove|assign|unassign|update args
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut positionals: Vec<String> = Vec::new();
+    let mut project_name: Option<String> = None;
+    let mut spreadsheet_path: Option<PathBuf> = None;
+    let mut objective = Objective::default();
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("-") {
+            positionals.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "--project" => project_name = args.next(),
+            "--sheet" => spreadsheet_path = args.next().map(PathBuf::from),
+            "--description" => objective.description = args.next().unwrap_or_default(),
+            "--due" => {
+                let arg = args.next().unwrap_or_default();
+                objective.due = match NaiveDate::parse_from_str(&arg, "%Y-%m-%d") {
+                    Ok(date) => Some(date),
+                    Err(err) => {
+                        printerror!("Invalid due date \"{arg}\". Expected YYYY-MM-DD. {err}");
+                        return Err(());
+                    }
+                };
+            },
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+    if positionals.is_empty() {
+        print_help();
+        return Ok(());
+    }
+
+    let mut project = Project::find(project_name.as_deref()).ok_or(())?;
+    let spreadsheet_path = spreadsheet_path.unwrap_or(project.spreadsheet_path());
+    printinfo!(be_verbose, "Using project {} ({:?}).", project.name, project.path);
+
+    let command = positionals.remove(0);
+    // Every command but unassign and update starts with a version.
+    let version: Option<Version> = match (command.as_str(), positionals.first()) {
+        ("add" | "remove" | "assign", Some(version)) => match version.parse() {
+            Ok(version) => Some(version),
+            Err(err) => {
+                printerror!("Invalid objective version. {err}");
+                return Err(());
+            }
+        },
+        _ => None,
+    };
+    match (command.as_str(), positionals.as_slice(), version) {
+        ("list", [], _) => {
+            let sheet = if spreadsheet_path.is_file() { EditableFile::open(&spreadsheet_path, be_verbose) } else { None };
+            if let Some(sheet) = &sheet {
+                check_objectives(&sheet.requirements);
+            }
+            for (version, objective) in &project.objectives {
+                let mut line = format!("{version} {}", objective.name);
+                if let Some(sheet) = &sheet {
+                    let reqs: Vec<_> = sheet.requirements.iter().filter(|x| objective_version(x).as_ref() == Some(version)).collect();
+                    line += &format!(" ({}/{} done)", reqs.iter().filter(|x| x.is_complete()).count(), reqs.len());
+                }
+                if let Some(due) = objective.due {
+                    line += &format!(", due {due}");
+                }
+                if let Some(completed) = objective.completed {
+                    line += &format!(", completed {completed}");
+                }
+                println!("{line}");
+            }
+        },
+        ("add", [_, name], Some(version)) => {
+            objective.name = name.to_string();
+            project.add_objective(&version, objective).ok_or(())?;
+            project.save().ok_or(())?;
+            printinfo!("Added objective {version} to {}.", project.name);
+        },
+        ("remove", [_], Some(version)) => {
+            project.remove_objective(&version).ok_or(())?;
+            // Unassign anything left over.
+            if spreadsheet_path.is_file() {
+                let mut sheet = EditableFile::open(&spreadsheet_path, be_verbose).ok_or(())?;
+                let hashes: Vec<String> = sheet.requirements.iter()
+                    .filter(|x| objective_version(x).as_ref() == Some(&version))
+                    .map(|x| x.hash.clone())
+                    .collect();
+                for hash in &hashes {
+                    sheet.set_objective(hash, None).ok_or(())?;
+                }
+                save(&sheet)?;
+                printinfo!(!hashes.is_empty(), "Unassigned {} requirement(s).", hashes.len());
+            }
+            project.save().ok_or(())?;
+            printinfo!("Removed objective {version} from {}.", project.name);
+        },
+        ("assign", [_, patterns @ ..], Some(version)) if !patterns.is_empty() => {
+            if !project.objectives.contains_key(&version) {
+                printerror!("Objective {version} does not exist. Add it with: ody objective add {version} name");
+                return Err(());
+            }
+            let mut sheet = EditableFile::open(&spreadsheet_path, be_verbose).ok_or(())?;
+            let hashes = assign_objective(&mut sheet, patterns, Some(&version)).ok_or(())?;
+            save(&sheet)?;
+            printinfo!("Assigned {} requirement(s) to {version}.", hashes.len());
+        },
+        ("unassign", patterns, _) if !patterns.is_empty() => {
+            let mut sheet = EditableFile::open(&spreadsheet_path, be_verbose).ok_or(())?;
+            let hashes = assign_objective(&mut sheet, patterns, None).ok_or(())?;
+            save(&sheet)?;
+            printinfo!("Unassigned {} requirement(s).", hashes.len());
+        },
+        ("update", [], _) => {
+            let sheet = EditableFile::open(&spreadsheet_path, be_verbose).ok_or(())?;
+            if !check_objectives(&sheet.requirements) {
+                return Err(());
+            }
+            let previous = project.version.clone();
+            let completed = project.update_objectives(&sheet.requirements, Local::now().date_naive());
+            for version in &completed {
+                printinfo!("Completed objective {version}.");
+            }
+            if project.version != previous {
+                printinfo!("{} is now at version {}.", project.name, project.version.as_ref().unwrap());
+            }
+            let is_waiting = project.objectives.iter().any(|(version, x)| x.completed.is_some() && project.version.as_ref().is_none_or(|x| x < version));
+            if let Some((version, _)) = project.objectives.iter().find(|(_, x)| x.completed.is_none()).filter(|_| is_waiting) {
+                printinfo!("The version stays below {version} until that objective is complete.");
+            }
+            project.save().ok_or(())?;
+        },
+        _ => {
+            printerror!("Unexpected arguments for ody objective {command}.");
+            print_help();
+            return Err(());
+        }
+    }
+    return Ok(());
+}
+
+fn save(sheet: &EditableFile) -> Result<(), ()> {
+    let mut transaction = Transaction::new();
+    sheet.save(&mut transaction).ok_or(())?;
+    return transaction.commit().ok_or(());
+}
+
+fn print_help() {
+    println!("Manages a project's objectives. Objectives are stored in the project file, and assigned to requirements in its spreadsheet's Objective column.");
+    println!("ody objective [options] list");
+    println!("ody objective [options] add version name");
+    println!("ody objective [options] remove version");
+    println!("ody objective [options] assign version pattern...");
+    println!("ody objective [options] unassign pattern...");
+    println!("ody objective [options] update");
+    println!("\nPatterns are ids, where * matches within one level and ** matches any number of levels. E.g. PROJ.1.* matches the children of PROJ.1.");
+    println!("Versions follow semantic versioning, e.g. 1.0.0 or 1.0.0-beta. Update marks objectives whose requirements are all done as completed, then moves the project to the highest version reached without skipping an incomplete objective (unless advance_out_of_order is set in the project file).");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n--project name\t\tProject to use. Defaults to the project whose working directory contains the current directory.\n--sheet path\t\tSpreadsheet to use. Defaults to $working_dir/requirements.csv.\n--description text\tDescription of a new objective.\n--due date\t\tDue date (YYYY-MM-DD) of a new objective.\n-v | --verbose\t\tPrint extra information.");
+}
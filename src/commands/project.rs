@@ -0,0 +1,54 @@
+use mythos_core::{printerror, printinfo};
+
+use crate::project::{open_environment, DryRunSpawner, ProcessSpawner, Project, Spawner};
+
+/// ody -p command [options] args
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut positionals: Vec<String> = Vec::new();
+    let mut do_dry_run = false;
+    let mut be_verbose = false;
+
+    for arg in args {
+        if !arg.starts_with("-") {
+            positionals.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "-n" | "--dry-run" => do_dry_run = true,
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+    if positionals.is_empty() {
+        print_help();
+        return Ok(());
+    }
+
+    let command = positionals.remove(0);
+    match (command.as_str(), positionals.as_slice()) {
+        ("open", [] | [_]) => {
+            let project = Project::find(positionals.first().map(|x| x.as_str())).ok_or(())?;
+            printinfo!(be_verbose, "Using project {} ({:?}).", project.name, project.path);
+            let mut spawner: Box<dyn Spawner> = if do_dry_run { Box::new(DryRunSpawner) } else { Box::new(ProcessSpawner) };
+            let count = open_environment(&project, spawner.as_mut()).ok_or(())?;
+            printinfo!(count == 0, "Nothing to open for project {}. Add terminals or files under [environment] in {:?}.", project.name, project.path);
+            printinfo!(be_verbose && count > 0, "Opened {count} program(s).");
+        },
+        _ => {
+            printerror!("Unexpected arguments for ody -p {command}.");
+            print_help();
+            return Err(());
+        }
+    }
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Manages projects.");
+    println!("ody -p open [project]\tOpen the terminals and files in the project's [environment]. Defaults to the project whose working directory contains the current directory.");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-n | --dry-run\t\tPrint the commands instead of running them.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use mythos_core::printerror;
+
+use crate::requirements::{parse_requirement_set, Filter, Requirement};
+use crate::{is_std_stream, Format};
+
+/// ody query [options] requirements_file [filter]
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut input_path: Option<PathBuf> = None;
+    let mut filter: Option<Filter> = None;
+    let mut format = Format::Text;
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-" || !arg.starts_with("-") {
+            if input_path.is_none() {
+                input_path = Some(PathBuf::from(arg));
+            } else if filter.is_none() {
+                filter = Some(Filter::parse(&arg).ok_or(())?);
+            } else {
+                printerror!("Unexpected argument {arg:?}. Filters containing spaces must be quoted.");
+                return Err(());
+            }
+            continue;
+        }
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = match args.next().unwrap_or("".to_string()).as_str() {
+                    "text" => Format::Text,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    "yaml" => Format::Yaml,
+                    _ => {
+                        printerror!("-f/--format must be accompanied with one of: text, csv, json, yaml.");
+                        return Err(());
+                    }
+                };
+            },
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+
+    let input_path = match input_path {
+        Some(path) if is_std_stream(&path) || path.is_file() => path,
+        Some(path) => {
+            printerror!("Input file {path:?} does not exist.");
+            return Err(());
+        },
+        None => {
+            printerror!("User must provide an input path.");
+            return Err(());
+        }
+    };
+    let mut set = parse_requirement_set(&input_path, be_verbose).ok_or(())?;
+    set.retain_matching(filter.as_ref());
+
+    let output = match format {
+        Format::Json => set.to_json(),
+        Format::Yaml => set.to_yaml(),
+        Format::Csv => Some(Requirement::get_csv_header().to_string()
+            + &set.requirements.iter().map(|x| x.to_csv_format()).collect::<String>()),
+        _ => Some(set.requirements.iter()
+            .map(|x| format!("{} {} {}: {}(@{})\n", x.category, x.id_to_string(), x.status_label(), x.contents, x.hash))
+            .collect()),
+    };
+    print!("{}", output.ok_or(())?);
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Prints the requirements matching a filter.");
+    println!("ody query [options] requirements_file [filter]");
+    println!("\nFilters compare a field (category, id, hash, contents, status, objective) to a value, and can be combined with and, or, not and parentheses.");
+    println!("Operators: = equals, != not equals, ^= starts with, ~ contains, !~ does not contain.");
+    println!("E.g. ody query reqs.md 'category=PROJ and id^=1 and status!=done and contents~\"terminal\"'");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-f | --format fmt\tOutput format: text (default), csv, json or yaml.\n-v | --verbose\t\tPrint extra information.");
+}
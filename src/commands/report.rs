@@ -0,0 +1,76 @@
+use std::{io::Write, path::PathBuf};
+
+use mythos_core::{printerror, printinfo};
+
+use crate::requirements::{parse_requirement_set, Filter};
+use crate::{is_std_stream, open_writer};
+
+/// ody report [options] requirements_file
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut input_path: Option<PathBuf> = None;
+    let mut html_path: Option<PathBuf> = None;
+    let mut filter: Option<Filter> = None;
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-" || !arg.starts_with("-") {
+            if input_path.is_some() {
+                printerror!("Unexpected argument {arg:?}.");
+                return Err(());
+            }
+            input_path = Some(PathBuf::from(arg));
+            continue;
+        }
+        match arg.as_str() {
+            "--html" => {
+                let arg = args.next().unwrap_or("".to_string());
+                if arg.is_empty() || (arg.starts_with("-") && arg != "-") {
+                    printerror!("--html must be accompanied with a file path.");
+                    return Err(());
+                }
+                html_path = Some(PathBuf::from(arg));
+            },
+            "--where" => filter = Some(Filter::parse(&args.next().unwrap_or("".to_string())).ok_or(())?),
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+
+    let input_path = match input_path {
+        Some(path) if is_std_stream(&path) || path.is_file() => path,
+        Some(path) => {
+            printerror!("Input file {path:?} does not exist.");
+            return Err(());
+        },
+        None => {
+            printerror!("User must provide an input path.");
+            return Err(());
+        }
+    };
+    let html_path = html_path.unwrap_or(PathBuf::from("-"));
+    let mut reqs = parse_requirement_set(&input_path, be_verbose).ok_or(())?;
+    reqs.retain_matching(filter.as_ref());
+
+    let title = match input_path.file_stem().and_then(|x| x.to_str()) {
+        Some(stem) if !is_std_stream(&input_path) => stem.to_string(),
+        _ => "Requirements".to_string(),
+    };
+    printinfo!(be_verbose, "Writing report to {html_path:?}");
+    let mut writer = open_writer(&html_path).ok_or(())?;
+    if let Err(err) = writer.write_all(reqs.to_html_report(&title).as_bytes()).and_then(|_| writer.flush()) {
+        printerror!("Error while writing report. {err}");
+        return Err(());
+    }
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Writes a self-contained html page showing the progress of a requirements list or spreadsheet.");
+    println!("ody report [options] requirements_file");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n--html path\t\tWrite report to $path. Defaults to stdout.\n--where filter\t\tOnly include requirements matching $filter. See ody query --help.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,55 @@
+use std::{io::Write, path::PathBuf};
+
+use mythos_core::{printerror, printinfo};
+
+use crate::open_writer;
+use crate::project::{check_objectives, Project, Roadmap};
+use crate::requirements::parse_requirement_set;
+
+/// ody roadmap [options]
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut project_name: Option<String> = None;
+    let mut spreadsheet_path: Option<PathBuf> = None;
+    let mut output_path = PathBuf::from("-");
+    let mut use_markdown_output = false;
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--project" => project_name = args.next(),
+            "--sheet" => spreadsheet_path = args.next().map(PathBuf::from),
+            "-o" | "--output" => output_path = PathBuf::from(args.next().unwrap_or("-".to_string())),
+            "-m" | "--markdown" => use_markdown_output = true,
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+
+    let project = Project::find(project_name.as_deref()).ok_or(())?;
+    let spreadsheet_path = spreadsheet_path.unwrap_or(project.spreadsheet_path());
+    printinfo!(be_verbose, "Reading {spreadsheet_path:?} for project {}.", project.name);
+    let reqs = parse_requirement_set(&spreadsheet_path, be_verbose).ok_or(())?;
+    if !check_objectives(&reqs.requirements) {
+        return Err(());
+    }
+
+    let roadmap = Roadmap::new(&project, &reqs.requirements);
+    let output = if use_markdown_output { roadmap.to_md_format() } else { roadmap.to_text_format() };
+    let mut writer = open_writer(&output_path).ok_or(())?;
+    if let Err(err) = writer.write_all(output.as_bytes()).and_then(|_| writer.flush()) {
+        printerror!("Error while writing roadmap. {err}");
+        return Err(());
+    }
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Shows a project's objectives in version order, along with the requirements left to do for each.");
+    println!("ody roadmap [options]");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n--project name\t\tProject to use. Defaults to the project whose working directory contains the current directory.\n--sheet path\t\tSpreadsheet to use. Defaults to $working_dir/requirements.csv.\n-m | --markdown\t\tWrite as markdown, e.g. to paste into a README.\n-o | --output path\tWrite to $path. Defaults to stdout.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,74 @@
+use std::path::PathBuf;
+
+use mythos_core::printerror;
+
+use crate::requirements::{parse_requirement_set, Filter, ProgressReport};
+use crate::{is_std_stream, Format};
+
+/// ody stats [options] requirements_file
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut input_path: Option<PathBuf> = None;
+    let mut format = Format::Text;
+    let mut filter: Option<Filter> = None;
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "-" || !arg.starts_with("-") {
+            if input_path.is_some() {
+                printerror!("Unexpected argument {arg:?}.");
+                return Err(());
+            }
+            input_path = Some(PathBuf::from(arg));
+            continue;
+        }
+        match arg.as_str() {
+            "-f" | "--format" => {
+                format = match args.next().unwrap_or("".to_string()).as_str() {
+                    "text" => Format::Text,
+                    "json" => Format::Json,
+                    "yaml" => Format::Yaml,
+                    _ => {
+                        printerror!("-f/--format must be accompanied with one of: text, json, yaml.");
+                        return Err(());
+                    }
+                };
+            },
+            "--where" => filter = Some(Filter::parse(&args.next().unwrap_or("".to_string())).ok_or(())?),
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+
+    let input_path = match input_path {
+        Some(path) if is_std_stream(&path) || path.is_file() => path,
+        Some(path) => {
+            printerror!("Input file {path:?} does not exist.");
+            return Err(());
+        },
+        None => {
+            printerror!("User must provide an input path.");
+            return Err(());
+        }
+    };
+    let mut reqs = parse_requirement_set(&input_path, be_verbose).ok_or(())?;
+    reqs.retain_matching(filter.as_ref());
+    let report = ProgressReport::new(&reqs);
+    let output = match format {
+        Format::Json => report.to_json_format(),
+        Format::Yaml => report.to_yaml_format(),
+        _ => Some(report.to_text_format()),
+    };
+    print!("{}", output.ok_or(())?);
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Shows how many requirements are done, per category, top level requirement and objective.");
+    println!("ody stats [options] requirements_file");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-f | --format fmt\tOutput format: text (default), json or yaml.\n--where filter\t\tOnly include requirements matching $filter. See ody query --help.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,92 @@
+use chrono::Local;
+use mythos_core::{printerror, printinfo, printwarn};
+
+use crate::requirements::{append_history, find_requirement, history_path, Requirement, StatusChange, StatusSource, Transaction};
+
+/**
+ * ody set-status [options] requirements_file [spreadsheet] target status
+ * ody done/reopen [options] requirements_file [spreadsheet] target
+ * status is given by the subcommand for done/reopen.
+ */
+pub fn run(args: impl Iterator<Item = String>, status: Option<u8>) -> Result<(), ()> {
+    let mut positionals: Vec<String> = Vec::new();
+    let mut be_verbose = false;
+
+    for arg in args {
+        // "-" is a valid list mark.
+        if arg == "-" || !arg.starts_with("-") {
+            positionals.push(arg);
+            continue;
+        }
+        match arg.as_str() {
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+
+    // Status comes last for set-status.
+    let status = match status {
+        Some(status) => status,
+        None => match positionals.pop() {
+            Some(value) => match Requirement::parse_status(&value) {
+                Some(status) => status,
+                None => {
+                    printerror!("Invalid status \"{value}\". Use open, done, a number (0-255) or a list mark.");
+                    return Err(());
+                }
+            },
+            None => {
+                print_help();
+                return Err(());
+            }
+        }
+    };
+    let (input_path, spreadsheet_path, rest) = super::split_files(positionals, 1)?;
+    let (mut list, mut sheet) = super::open_files(&input_path, spreadsheet_path, be_verbose)?;
+
+    let req = find_requirement(&list.requirements, &rest[0]).ok_or(())?.clone();
+    let now = Local::now().fixed_offset();
+    let is_changed = req.status != status;
+    let mut transaction = Transaction::new();
+    list.set_status(&req.hash, status).ok_or(())?;
+    if list.is_spreadsheet() && is_changed {
+        list.set_completed_at(&req.hash, Some(now).filter(|_| status == 1)).ok_or(())?;
+    }
+    list.save(&mut transaction).ok_or(())?;
+    // The change is recorded in the history of whichever spreadsheet was edited.
+    let mut history = list.is_spreadsheet().then(|| history_path(&list.path));
+    if let Some(sheet) = &mut sheet {
+        match sheet.set_status(&req.hash, status) {
+            Some(_) => {
+                if is_changed {
+                    sheet.set_completed_at(&req.hash, Some(now).filter(|_| status == 1)).ok_or(())?;
+                }
+                sheet.save(&mut transaction).ok_or(())?;
+                history = Some(history_path(&sheet.path));
+            },
+            None => printwarn!("{} is not in {:?} yet. It will be added on the next sync.", req.hash, sheet.path),
+        }
+    }
+    transaction.commit().ok_or(())?;
+    if let Some(history) = history.filter(|_| is_changed) {
+        append_history(&history, &[StatusChange::new(&req.hash, Some(req.status), status, now, StatusSource::Cli)]).ok_or(())?;
+    }
+
+    let updated = list.get(&req.hash).ok_or(())?;
+    printinfo!("{} {} {}: {} -> {}", req.category, req.id_to_string(), req.contents, req.status_label(), updated.status_label());
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Changes the status of a requirement in both the requirements file and spreadsheet, without touching anything else.");
+    println!("ody set-status [options] requirements_file [spreadsheet] hash|id status");
+    println!("ody done [options] requirements_file [spreadsheet] hash|id");
+    println!("ody reopen [options] requirements_file [spreadsheet] hash|id");
+    println!("\nRequirements can be given by hash or id. Ids can be prefixed with their category, e.g. PROJ.1.2.");
+    println!("Status can be open, done, failed, a label from odysseus.toml, a number (0-255) or a list mark. If no spreadsheet is given, ./<dir-name>.csv is updated if it exists.");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-v | --verbose\t\tPrint extra information.");
+}
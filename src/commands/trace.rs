@@ -0,0 +1,110 @@
+use std::path::PathBuf;
+
+use mythos_core::{printerror, printinfo};
+
+use crate::project::Project;
+use crate::requirements::{is_document, scan_sources, EditableFile, Trace, Transaction};
+use crate::Format;
+
+/// ody trace [options] src_dir...
+pub fn run(args: impl Iterator<Item = String>) -> Result<(), ()> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    let mut project_name: Option<String> = None;
+    let mut spreadsheet_path: Option<PathBuf> = None;
+    let mut format = Format::Text;
+    let mut do_write = false;
+    let mut is_strict = false;
+    let mut be_verbose = false;
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if !arg.starts_with("-") {
+            dirs.push(PathBuf::from(arg));
+            continue;
+        }
+        match arg.as_str() {
+            "--project" => project_name = args.next(),
+            "--sheet" => spreadsheet_path = args.next().map(PathBuf::from),
+            "-f" | "--format" => {
+                format = match args.next().unwrap_or("".to_string()).as_str() {
+                    "text" => Format::Text,
+                    "csv" => Format::Csv,
+                    "json" => Format::Json,
+                    _ => {
+                        printerror!("-f/--format must be accompanied with one of: text, csv, json.");
+                        return Err(());
+                    }
+                };
+            },
+            "--write" => do_write = true,
+            "--strict" => is_strict = true,
+            "-v" | "--verbose" => be_verbose = true,
+            "-h" | "--help" | _ => {
+                print_help();
+                return Ok(());
+            }
+        }
+    }
+    if dirs.is_empty() {
+        print_help();
+        return Ok(());
+    }
+
+    let spreadsheet_path = match spreadsheet_path {
+        Some(path) => path,
+        None => {
+            let project = Project::find(project_name.as_deref()).ok_or(())?;
+            printinfo!(be_verbose, "Using project {} ({:?}).", project.name, project.path);
+            project.spreadsheet_path()
+        }
+    };
+    let mut sheet = EditableFile::open(&spreadsheet_path, be_verbose).ok_or(())?;
+    let mut tags = Vec::new();
+    for dir in &dirs {
+        printinfo!(be_verbose, "Scanning {dir:?}.");
+        tags.extend(scan_sources(dir).ok_or(())?);
+    }
+    let trace = Trace::new(&sheet.requirements, tags);
+
+    let output = match format {
+        Format::Csv => Some(trace.to_csv_format()),
+        Format::Json => trace.to_json_format(),
+        _ => Some(trace.to_text_format()),
+    };
+    print!("{}", output.ok_or(())?);
+
+    if do_write {
+        if !sheet.is_spreadsheet() && !is_document(&spreadsheet_path) {
+            printerror!("{spreadsheet_path:?} has no Trace column. Pass a spreadsheet (or json/yaml file) with --sheet.");
+            return Err(());
+        }
+        let mut changed = 0;
+        for entry in &trace.requirements {
+            let column = entry.to_column();
+            if sheet.get(&entry.hash).is_some_and(|x| x.trace != column) {
+                sheet.set_trace(&entry.hash, column.as_deref()).ok_or(())?;
+                changed += 1;
+            }
+        }
+        if changed > 0 {
+            let mut transaction = Transaction::new();
+            sheet.save(&mut transaction).ok_or(())?;
+            transaction.commit().ok_or(())?;
+        }
+        printinfo!(!matches!(format, Format::Json | Format::Csv), "Updated the Trace column of {changed} requirement(s) in {spreadsheet_path:?}.");
+    }
+
+    if is_strict && (trace.unimplemented().next().is_some() || trace.untested().next().is_some() || !trace.unknown.is_empty()) {
+        printerror!("Some requirements are missing implementations or tests, or some tags are unknown.");
+        return Err(());
+    }
+    return Ok(());
+}
+
+fn print_help() {
+    println!("Finds requirements referenced in source code, by tags like // REQ(@hash) or // REQ: PROJ.1.2.");
+    println!("ody trace [options] src_dir...");
+    println!("\nTags in test/tests/spec directories, files named like tests (test_x.py, x_test.go, x.test.ts) or after #[cfg(test)] count as tests, the rest as implementations. Prints every requirement with its locations, then the requirements without implementations or tests, and tags that don't match a requirement.");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n--project name\t\tProject to use. Defaults to the project whose working directory contains the current directory.\n--sheet path\t\tRequirements file or spreadsheet to trace. Defaults to the project's spreadsheet.\n-f | --format fmt\tOutput format: text (default), csv or json.\n--write\t\t\tWrite the locations to the spreadsheet's Trace column.\n--strict\t\tFail if a requirement has no implementation or test, or a tag is unknown.\n-v | --verbose\t\tPrint extra information.");
+}
@@ -0,0 +1,147 @@
+use std::{fs, hash::{DefaultHasher, Hash, Hasher}, path::PathBuf, thread::sleep, time::Duration};
+use chrono::Local;
+use mythos_core::printerror;
+
+use crate::{is_std_stream, sync, SyncOptions};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// How long the files must stay the same after a change before syncing, so a save made in several writes syncs once.
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/**
+ * Tells when files' contents change. Contents are compared rather than modification times, so
+ * rewriting a file with the same contents isn't a change, and the sync's own writes can be ignored
+ * by marking what was written as seen.
+ */
+pub struct Watcher {
+    paths: Vec<PathBuf>,
+    seen: Vec<Option<u64>>,
+}
+
+impl Watcher {
+    pub fn new(paths: Vec<PathBuf>) -> Watcher {
+        let seen = paths.iter().map(fingerprint).collect();
+        return Watcher { paths, seen };
+    }
+
+    /// Files whose contents differ from when they were last seen.
+    pub fn changed(&self) -> Vec<PathBuf> {
+        return self.paths.iter().zip(&self.seen)
+            .filter(|(path, seen)| fingerprint(path) != **seen)
+            .map(|(path, _)| path.clone())
+            .collect();
+    }
+
+    /**
+     * Treat files as unchanged if they have the contents that were written to them. Files edited since
+     * still count as changed, as do the other files.
+     */
+    pub fn mark_written(&mut self, written: &[(PathBuf, Vec<u8>)]) {
+        for (path, contents) in written {
+            if let Some(i) = self.paths.iter().position(|x| x == path) {
+                self.seen[i] = Some(hash(contents));
+            }
+        }
+    }
+
+    /// Block until a file has changed and none of them have changed again for $debounce.
+    pub fn wait(&self, poll: Duration, debounce: Duration) -> Vec<PathBuf> {
+        loop {
+            sleep(poll);
+            if self.changed().is_empty() {
+                continue;
+            }
+            let mut current: Vec<Option<u64>> = self.paths.iter().map(fingerprint).collect();
+            loop {
+                sleep(debounce);
+                let next: Vec<Option<u64>> = self.paths.iter().map(fingerprint).collect();
+                if next == current {
+                    break;
+                }
+                current = next;
+            }
+            // A change can be undone before it settles, e.g. by an editor's temporary file.
+            let changed = self.changed();
+            if !changed.is_empty() {
+                return changed;
+            }
+        }
+    }
+}
+
+/// Hash of a file's contents. None if it can't be read, e.g. while an editor replaces it.
+fn fingerprint(path: &PathBuf) -> Option<u64> {
+    return fs::read(path).ok().map(|x| hash(&x));
+}
+
+fn hash(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    return hasher.finish();
+}
+
+/// ody --watch [options] requirements_file [spreadsheet]
+pub fn run(options: &SyncOptions) -> Result<(), ()> {
+    let output_path = options.output_path();
+    if is_std_stream(&options.input_path) || is_std_stream(&output_path) {
+        printerror!("--watch can't be used with stdin or stdout.");
+        return Err(());
+    }
+    let mut paths = vec![options.input_path.clone(), output_path];
+    if let Some(path) = options.spreadsheet_path.as_ref().filter(|x| !paths.contains(x)) {
+        paths.push(path.clone());
+    }
+    paths.extend(options.test_results.iter().cloned());
+    let mut watcher = Watcher::new(paths.clone());
+
+    // Errors are reported by sync. Keep watching, so they can be fixed and synced on the next save.
+    let mut written = Vec::new();
+    let _ = sync(options, &mut written);
+    watcher.mark_written(&written);
+    println!("Watching {}. Press Ctrl+C to stop.", paths.iter().map(|x| format!("{x:?}")).collect::<Vec<String>>().join(" and "));
+    loop {
+        let changed = watcher.wait(POLL_INTERVAL, DEBOUNCE);
+        let changed: Vec<String> = changed.iter().map(|x| format!("{x:?}")).collect();
+        println!("\n[{}] {} changed. Syncing.", Local::now().format("%H:%M:%S"), changed.join(" and "));
+        let mut written = Vec::new();
+        let _ = sync(options, &mut written);
+        // Don't sync again because of the files just written. Edits saved during the sync are still picked up.
+        watcher.mark_written(&written);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_changes() {
+        let dir = std::env::temp_dir().join(format!("odysseus-watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let list = dir.join("reqs.md");
+        let sheet = dir.join("reqs.csv");
+        fs::write(&list, "Proj (PROJ)\n1. Thing\n").unwrap();
+        fs::write(&sheet, "").unwrap();
+
+        let mut watcher = Watcher::new(vec![list.clone(), sheet.clone()]);
+        assert!(watcher.changed().is_empty());
+        // Same contents, e.g. a save without edits.
+        fs::write(&list, "Proj (PROJ)\n1. Thing\n").unwrap();
+        assert!(watcher.changed().is_empty());
+
+        fs::write(&sheet, "Hash,Category\n").unwrap();
+        assert_eq!(watcher.wait(Duration::ZERO, Duration::from_millis(10)), vec![sheet.clone()]);
+        // Written by the sync itself, while the sheet was edited again.
+        let synced = b"Proj (PROJ)\n1. Thing (@1234)\n".to_vec();
+        fs::write(&list, &synced).unwrap();
+        fs::write(&sheet, "Hash,Category,Id\n").unwrap();
+        watcher.mark_written(&[(list.clone(), synced), (sheet.clone(), b"Hash,Category\n".to_vec())]);
+        assert_eq!(watcher.changed(), vec![sheet.clone()]);
+        watcher.mark_written(&[(sheet.clone(), b"Hash,Category,Id\n".to_vec())]);
+        assert!(watcher.changed().is_empty());
+
+        fs::remove_file(&list).unwrap();
+        assert_eq!(watcher.changed(), vec![list.clone()]);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,266 @@
+//! Defaults read from odysseus.toml. The one in the current repository takes precedence over the one in
+//! $MYTHOS_CONFIG_DIR/odysseus, setting by setting, and cli flags take precedence over both.
+use std::{collections::HashMap, fs, path::{Path, PathBuf}, sync::OnceLock};
+use mythos_core::{dirs::{get_dir, MythosDir}, printerror};
+use serde::Deserialize;
+use toml::Value;
+
+use crate::requirements::{HashAlgorithm, ListSettings};
+use crate::Format;
+
+pub const CONFIG_FILE_NAME: &str = "odysseus.toml";
+const FORMATS: [&str; 5] = ["csv", "markdown", "json", "yaml", "toml"];
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Every setting is optional. Unknown settings are an error, so typos don't go unnoticed.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    spreadsheet: Option<PathBuf>,
+    format: Option<String>,
+    overwrite: Option<bool>,
+    indent_width: Option<usize>,
+    hash: Option<String>,
+    #[serde(default)]
+    statuses: toml::Table,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    /// Used when no spreadsheet or -o path is given, instead of ./<dir-name>.csv. Relative to the repository config's directory.
+    pub spreadsheet: Option<PathBuf>,
+    /// Format of the spreadsheet written by a sync: csv, markdown, json, yaml or toml.
+    pub format: String,
+    /// Whether a sync rewrites the requirements file.
+    pub overwrite: bool,
+    pub list: ListSettings,
+    /// The file each setting was last set by. Settings that aren't here are defaults. Statuses are keyed "statuses.<label>".
+    pub sources: HashMap<String, PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        return Config {
+            spreadsheet: None,
+            format: "csv".to_string(),
+            overwrite: true,
+            list: ListSettings::default(),
+            sources: HashMap::new(),
+        };
+    }
+}
+
+impl Config {
+    /// The settings in use. Defaults if init wasn't called.
+    pub fn get() -> &'static Config {
+        return CONFIG.get_or_init(Config::default);
+    }
+
+    /// Load the config files and use them for the rest of the run.
+    pub fn init() -> Option<()> {
+        let config = Config::load()?;
+        ListSettings::set(config.list.clone());
+        let _ = CONFIG.set(config);
+        return Some(());
+    }
+
+    /// The user config, then the repository config, for those that exist.
+    pub fn paths() -> Vec<PathBuf> {
+        return [user_config_path(), repository_config_path()].into_iter().flatten().filter(|x| x.is_file()).collect();
+    }
+
+    pub fn load() -> Option<Config> {
+        let mut config = Config::default();
+        for path in Config::paths() {
+            let contents = match fs::read_to_string(&path) {
+                Ok(contents) => contents,
+                Err(err) => {
+                    printerror!("Could not read config file {path:?}. {err}");
+                    return None;
+                }
+            };
+            config.apply(&path, &contents)?;
+        }
+        return Some(config);
+    }
+
+    /// Override the settings $contents sets. $path is recorded as their source.
+    fn apply(&mut self, path: &Path, contents: &str) -> Option<()> {
+        let file: ConfigFile = match toml::from_str(contents) {
+            Ok(file) => file,
+            Err(err) => {
+                printerror!("Could not parse config file {path:?}. {err}");
+                return None;
+            }
+        };
+        let mut set = |name: &str| {
+            self.sources.insert(name.to_string(), path.to_path_buf());
+        };
+
+        if let Some(spreadsheet) = file.spreadsheet {
+            // The user config isn't tied to a directory, so its paths are left relative to the current one.
+            let is_repository_config = Some(path) != user_config_path().as_deref();
+            self.spreadsheet = Some(match path.parent() {
+                Some(dir) if is_repository_config && spreadsheet.is_relative() => dir.join(spreadsheet),
+                _ => spreadsheet,
+            });
+            set("spreadsheet");
+        }
+        if let Some(format) = file.format {
+            if !FORMATS.contains(&format.as_str()) {
+                printerror!("Invalid format \"{format}\" in {path:?}. Expected one of: {}.", FORMATS.join(", "));
+                return None;
+            }
+            self.format = format;
+            set("format");
+        }
+        if let Some(overwrite) = file.overwrite {
+            self.overwrite = overwrite;
+            set("overwrite");
+        }
+        if let Some(indent_width) = file.indent_width {
+            self.list.indent_width = indent_width;
+            set("indent_width");
+        }
+        if let Some(hash) = file.hash {
+            let Some(hash_algorithm) = HashAlgorithm::parse(&hash) else {
+                printerror!("Invalid hash \"{hash}\" in {path:?}. Expected one of: default, fnv1a.");
+                return None;
+            };
+            self.list.hash_algorithm = hash_algorithm;
+            set("hash");
+        }
+        for (label, mark) in file.statuses {
+            // Not Requirement::parse_status, which would fix the list settings before they are loaded.
+            let status = match mark.as_str().map(|x| x.as_bytes()) {
+                Some([b' ']) => 0,
+                Some([b'x']) => 1,
+                Some([mark]) if mark.is_ascii() => *mark,
+                _ => {
+                    printerror!("Invalid status \"{label}\" in {path:?}. Expected a single character list mark, e.g. {label} = \"-\".");
+                    return None;
+                },
+            };
+            self.list.statuses.retain(|(x, _)| *x != label);
+            self.list.statuses.push((label.clone(), status));
+            set(&format!("statuses.{label}"));
+        }
+        return Some(());
+    }
+
+    /// Spreadsheet format and whether it is markdown, as -f and -m would set them.
+    pub fn sync_format(&self) -> (Option<Format>, bool) {
+        return match self.format.as_str() {
+            "json" => (Some(Format::Json), false),
+            "yaml" => (Some(Format::Yaml), false),
+            "toml" => (Some(Format::Toml), false),
+            "markdown" => (None, true),
+            _ => (None, false),
+        };
+    }
+
+    /// Every setting as toml, each followed by the file it came from or "default".
+    pub fn to_annotated_toml(&self) -> String {
+        let source = |name: &str| match self.sources.get(name) {
+            Some(path) => path.display().to_string(),
+            None => "default".to_string(),
+        };
+        let mut lines: Vec<(String, String)> = Vec::new();
+        lines.push(match &self.spreadsheet {
+            Some(path) => (format!("spreadsheet = {}", Value::from(path.display().to_string())), source("spreadsheet")),
+            None => {
+                let extension = match self.format.as_str() {
+                    "csv" => "csv",
+                    "markdown" => "csv.md",
+                    format => format,
+                };
+                (format!("# spreadsheet = \"<dir-name>.{extension}\""), source("spreadsheet"))
+            },
+        });
+        lines.push((format!("format = {}", Value::from(self.format.as_str())), source("format")));
+        lines.push((format!("overwrite = {}", self.overwrite), source("overwrite")));
+        lines.push((format!("indent_width = {}", self.list.indent_width), source("indent_width")));
+        lines.push((format!("hash = {}", Value::from(self.list.hash_algorithm.name())), source("hash")));
+        lines.push(("\n[statuses]".to_string(), String::new()));
+
+        // Built in labels are left out once their status is relabelled, or their label is given to another status.
+        let mut statuses: Vec<(String, u8)> = ListSettings::default_statuses().into_iter()
+            .filter(|(label, status)| self.list.label(*status).is_none() && self.list.status(label).is_none())
+            .collect();
+        statuses.extend(self.list.statuses.iter().cloned());
+        for (label, status) in statuses {
+            let mark = match status {
+                0 => ' ',
+                1 => 'x',
+                _ => status as char,
+            };
+            lines.push((format!("{label} = {}", Value::from(mark.to_string())), source(&format!("statuses.{label}"))));
+        }
+
+        let width = lines.iter().map(|(x, _)| x.len()).max().unwrap_or(0);
+        let mut output = String::new();
+        for (line, source) in lines {
+            if source.is_empty() {
+                output += &format!("{line}\n");
+            } else {
+                output += &format!("{line:width$}  # {source}\n");
+            }
+        }
+        return output;
+    }
+}
+
+/// $MYTHOS_CONFIG_DIR/odysseus/odysseus.toml
+fn user_config_path() -> Option<PathBuf> {
+    return get_dir(MythosDir::Config, "odysseus").map(|x| x.join(CONFIG_FILE_NAME));
+}
+
+/// The closest odysseus.toml in the current directory or its parents, without leaving the git repository.
+fn repository_config_path() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    for dir in cwd.ancestors() {
+        let path = dir.join(CONFIG_FILE_NAME);
+        if path.is_file() {
+            return Some(path);
+        }
+        if dir.join(".git").exists() {
+            break;
+        }
+    }
+    return None;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layered_settings() {
+        let user = PathBuf::from("/home/user/.config/odysseus/odysseus.toml");
+        let repository = PathBuf::from("/repo/odysseus.toml");
+        let mut config = Config::default();
+        config.apply(&user, "format = \"json\"\nhash = \"fnv1a\"\n[statuses]\nblocked = \"-\"\n").unwrap();
+        config.apply(&repository, "spreadsheet = \"docs/reqs.csv\"\nformat = \"markdown\"\noverwrite = false\nindent_width = 4\n[statuses]\ncomplete = \"x\"\n").unwrap();
+
+        assert_eq!(config.spreadsheet, Some(PathBuf::from("/repo/docs/reqs.csv")));
+        assert_eq!(config.sync_format(), (None, true));
+        assert!(!config.overwrite);
+        assert_eq!(config.list.indent_width, 4);
+        assert_eq!(config.list.hash_algorithm, HashAlgorithm::Fnv1a);
+        assert_eq!(config.list.statuses, vec![("blocked".to_string(), b'-'), ("complete".to_string(), 1)]);
+        assert_eq!(config.sources["format"], repository);
+        assert_eq!(config.sources["hash"], user);
+
+        let output = config.to_annotated_toml();
+        assert!(output.contains("format = \"markdown\""));
+        assert!(output.lines().any(|x| x.starts_with("hash = \"fnv1a\"") && x.ends_with("# /home/user/.config/odysseus/odysseus.toml")));
+        assert!(output.lines().any(|x| x.starts_with("open = \" \"") && x.ends_with("# default")));
+        // done was relabelled complete.
+        assert!(!output.contains("done ="));
+
+        assert!(config.apply(&repository, "format = \"xml\"").is_none());
+        assert!(config.apply(&repository, "[statuses]\nblocked = \"--\"").is_none());
+        assert!(config.apply(&repository, "colour = true").is_none());
+    }
+}
@@ -0,0 +1,230 @@
+//! Optional git integration. Runs the local git executable, so it is only needed when these are used.
+use std::{path::{Path, PathBuf}, process::Command};
+use chrono::{DateTime, FixedOffset};
+use mythos_core::printerror;
+
+/// Fields of a commit, separated by the ascii unit separator.
+const LOG_FORMAT: &str = "--format=%H%x1f%an%x1f%aI%x1f%s";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    pub author: String,
+    pub date: DateTime<FixedOffset>,
+    pub summary: String,
+}
+
+/// A line of a file, and the commit that last changed it. None if the line isn't committed yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlameLine {
+    pub commit: Option<Commit>,
+    pub contents: String,
+}
+
+impl Commit {
+    pub fn short_hash(&self) -> &str {
+        return &self.hash[..self.hash.len().min(7)];
+    }
+
+    fn parse(line: &str) -> Option<Commit> {
+        let [hash, author, date, summary] = line.splitn(4, '\x1f').collect::<Vec<&str>>()[..] else { return None };
+        return Some(Commit {
+            hash: hash.to_string(),
+            author: author.to_string(),
+            date: DateTime::parse_from_rfc3339(date).ok()?,
+            summary: summary.to_string(),
+        });
+    }
+}
+
+/// The directory to run git in and the path to pass it, so that paths relative to the current directory work.
+fn split_path(path: &Path) -> (PathBuf, String) {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    return (dir, format!("./{}", path.file_name().unwrap_or_default().to_string_lossy()));
+}
+
+/// Run git in $dir and return its output. Err holds git's error message.
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = match Command::new("git").arg("-C").arg(dir).args(args).output() {
+        Ok(output) => output,
+        Err(err) => return Err(format!("Could not run git. {err}")),
+    };
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    return Ok(String::from_utf8_lossy(&output.stdout).to_string());
+}
+
+/// Whether $path is in a git work tree. False if git isn't installed.
+pub fn is_in_repository(path: &Path) -> bool {
+    let (dir, _) = split_path(path);
+    return git(&dir, &["rev-parse", "--is-inside-work-tree"]).is_ok_and(|x| x.trim() == "true");
+}
+
+/// Whether $path has changes that aren't committed, including not being tracked at all. Missing files have none.
+pub fn has_uncommitted_changes(path: &Path) -> Option<bool> {
+    let (dir, name) = split_path(path);
+    return match git(&dir, &["status", "--porcelain", "--", &name]) {
+        Ok(output) => Some(!output.trim().is_empty()),
+        Err(err) => {
+            printerror!("Could not get the git status of {path:?}. {err}");
+            None
+        }
+    };
+}
+
+/// Commits that changed $path, oldest first. With $search, only those that added or removed it (git log -S).
+pub fn log(path: &Path, search: Option<&str>) -> Option<Vec<Commit>> {
+    let (dir, name) = split_path(path);
+    let search = search.map(|x| format!("-S{x}"));
+    let mut args = vec!["log", "--reverse", LOG_FORMAT];
+    args.extend(search.as_deref());
+    args.extend(["--", &name]);
+    return match git(&dir, &args) {
+        Ok(output) => output.lines().map(Commit::parse).collect(),
+        Err(err) => {
+            printerror!("Could not read the git log of {path:?}. {err}");
+            None
+        }
+    };
+}
+
+/// The commit that last changed each line of $path, as it is in the work tree.
+pub fn blame(path: &Path) -> Option<Vec<BlameLine>> {
+    let (dir, name) = split_path(path);
+    let output = match git(&dir, &["blame", "--line-porcelain", "--", &name]) {
+        Ok(output) => output,
+        Err(err) => {
+            printerror!("Could not run git blame on {path:?}. {err}");
+            return None;
+        }
+    };
+
+    // Each line is a header line starting with the commit hash, then "key value" lines, then a tab and the line itself.
+    let mut lines: Vec<BlameLine> = Vec::new();
+    let (mut hash, mut author, mut time, mut timezone, mut summary) = ("", "", "", "", "");
+    for line in output.lines() {
+        if let Some(contents) = line.strip_prefix('\t') {
+            let commit = if hash.chars().all(|x| x == '0') {
+                None
+            } else {
+                Some(Commit {
+                    hash: hash.to_string(),
+                    author: author.to_string(),
+                    date: parse_blame_time(time, timezone)?,
+                    summary: summary.to_string(),
+                })
+            };
+            lines.push(BlameLine { commit, contents: contents.to_string() });
+            hash = "";
+            continue;
+        }
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "author" => author = value,
+            "author-time" => time = value,
+            "author-tz" => timezone = value,
+            "summary" => summary = value,
+            _ if hash.is_empty() => hash = key,
+            _ => (),
+        }
+    }
+    return Some(lines);
+}
+
+/// Seconds since the epoch and a timezone like +0200.
+fn parse_blame_time(time: &str, timezone: &str) -> Option<DateTime<FixedOffset>> {
+    let offset = DateTime::parse_from_str(&format!("0 {timezone}"), "%s %z").ok()?.offset().to_owned();
+    return Some(DateTime::from_timestamp(time.parse().ok()?, 0)?.with_timezone(&offset));
+}
+
+/// Contents of $path at a revision, e.g. a commit hash.
+pub fn show(path: &Path, revision: &str) -> Option<String> {
+    let (dir, name) = split_path(path);
+    return match git(&dir, &["show", &format!("{revision}:{name}")]) {
+        Ok(output) => Some(output),
+        Err(err) => {
+            printerror!("Could not read {path:?} at {revision}. {err}");
+            None
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    /// A throwaway repository, removed when dropped.
+    struct Repository(PathBuf);
+
+    impl Repository {
+        fn new(name: &str) -> Repository {
+            let dir = std::env::temp_dir().join(format!("odysseus-git-{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            let repo = Repository(dir);
+            repo.run(&["init", "-q"]);
+            return repo;
+        }
+
+        fn run(&self, args: &[&str]) -> String {
+            let config = ["-c", "user.name=Tester", "-c", "user.email=tester@example.com", "-c", "commit.gpgsign=false"];
+            return git(&self.0, &[&config[..], args].concat()).unwrap();
+        }
+
+        fn commit(&self, file: &str, contents: &str, message: &str) -> String {
+            fs::write(self.0.join(file), contents).unwrap();
+            self.run(&["add", file]);
+            self.run(&["commit", "-q", "-m", message]);
+            return self.run(&["rev-parse", "HEAD"]).trim().to_string();
+        }
+    }
+
+    impl Drop for Repository {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn uncommitted_changes() {
+        let repo = Repository::new("status");
+        let path = repo.0.join("reqs.md");
+        assert!(is_in_repository(&path));
+        assert!(!is_in_repository(&std::env::temp_dir().join("odysseus-not-a-repo.md")));
+
+        fs::write(&path, "Proj (PROJ)\n1. Thing\n").unwrap();
+        assert_eq!(has_uncommitted_changes(&path), Some(true));
+        repo.commit("reqs.md", "Proj (PROJ)\n1. Thing\n", "Add list");
+        assert_eq!(has_uncommitted_changes(&path), Some(false));
+        fs::write(&path, "Proj (PROJ)\n1. Other thing\n").unwrap();
+        assert_eq!(has_uncommitted_changes(&path), Some(true));
+        assert_eq!(has_uncommitted_changes(&repo.0.join("missing.csv")), Some(false));
+    }
+    #[test]
+    fn blame_and_log() {
+        let repo = Repository::new("blame");
+        let path = repo.0.join("reqs.md");
+        let first = repo.commit("reqs.md", "Proj (PROJ)\n1. Thing (@h1)\n", "Add list");
+        let second = repo.commit("reqs.md", "Proj (PROJ)\n1. Thing (@h1)\n2. Other (@h2)\n", "Add other");
+        fs::write(&path, "Proj (PROJ)\n1. Thing (@h1)\n2. Renamed (@h2)\n").unwrap();
+
+        let lines = blame(&path).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].contents, "1. Thing (@h1)");
+        let commit = lines[1].commit.as_ref().unwrap();
+        assert_eq!((commit.hash.as_str(), commit.author.as_str(), commit.summary.as_str()), (first.as_str(), "Tester", "Add list"));
+        assert_eq!(lines[2].commit, None);
+
+        let added: Vec<String> = log(&path, Some("(@h2)")).unwrap().into_iter().map(|x| x.hash).collect();
+        assert_eq!(added, vec![second.clone()]);
+        assert_eq!(log(&path, None).unwrap().len(), 2);
+        assert_eq!(show(&path, &first).unwrap(), "Proj (PROJ)\n1. Thing (@h1)\n");
+        assert!(show(&path, "0000000").is_none());
+    }
+}
@@ -1,165 +1,373 @@
 mod requirements;
+mod project;
+mod commands;
+mod git;
+mod config;
 
 use std::ffi::OsString;
-use std::io::Write;
+use std::io::{stdin, stdout, IsTerminal, Write};
 use std::{collections::HashMap, io::BufWriter};
 use std::fs::File;
 use std::path::PathBuf;
 
+use chrono::Local;
 use mythos_core::logger::set_id;
 use mythos_core::printinfo;
 use mythos_core::{cli::clean_cli_args, printerror};
-use requirements::{parse_requirements, parse_spreadsheet, Requirement};
+use config::Config;
+use requirements::{diff_requirements, is_toml, to_toml_format, is_workbook, RequirementSet, write_workbook, merge_requirements, RequirementDiff, Authority, MergeRules, parse_requirements, parse_requirements_reader, parse_spreadsheet, parse_spreadsheet_reader, snapshot_path, Requirement, apply_history, append_history, detect_status_changes, history_path, read_history, StatusChange, apply_test_results, read_test_results, scan_function_tags, Filter};
+
+
+/// Output format selected with -f/--format.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Format { Text, Csv, Json, Yaml, Toml }
+
+impl Format {
+    /// Extensions a spreadsheet written as json/yaml/toml needs, so the next sync reads it back in that format.
+    fn document_extensions(&self) -> &'static [&'static str] {
+        return match self {
+            Format::Json => &["json"],
+            Format::Yaml => &["yaml", "yml"],
+            Format::Toml => &["toml"],
+            _ => &[],
+        };
+    }
+}
 
+/// Options of a sync, parsed from the cli args when no subcommand is given.
+struct SyncOptions {
+    input_path: PathBuf,
+    spreadsheet_path: Option<PathBuf>,
+    output_path: Option<PathBuf>,
+    overwrite_original_file: bool,
+    do_dry_run: bool,
+    be_verbose: bool,
+    use_markdown_output: bool,
+    /// Format of the spreadsheet, from -f or odysseus.toml.
+    format: Option<Format>,
+    /// Format of the -n report. Only set by -f, so odysseus.toml's format doesn't turn the report into json.
+    report_format: Option<Format>,
+    use_sheet_per_category: bool,
+    rules: MergeRules,
+    use_newest: bool,
+    watch: bool,
+    /// Refuse to overwrite files with uncommitted changes, unless forced.
+    check_git: bool,
+    force: bool,
+    /// JUnit XML or cargo test json files, whose results set the status of the requirements tagged in the tests.
+    test_results: Vec<PathBuf>,
+    /// Directories searched for tags in the comments above tests.
+    test_sources: Vec<PathBuf>,
+    /// Only write the matching requirements to -o path. Makes the sync a one way export.
+    filter: Option<Filter>,
+}
 
 fn main() -> Result<(), ()>{
     let _ = set_id("ODYSSEUS");
-    let mut input_path: Option<PathBuf> = None;
-    let input_data: Vec<Requirement>;
-
-    let mut output_path: Option<PathBuf> = None;
-    let mut output_data: HashMap<String, Requirement> = HashMap::new();
-
-    let mut overwrite_original_file = true;
-    let mut do_dry_run = false;
-    let mut be_verbose = false;
-    let mut use_markdown_output = false;
-
+    // Before anything is parsed, since the list settings can only be set once.
+    Config::init().ok_or(())?;
     let mut args = clean_cli_args().into_iter().peekable();
 
     if args.peek().is_none() {
         print_help();
         return Ok(());
     }
+    match args.peek().map(|x| x.as_str()) {
+        Some("report") => return commands::report::run(args.skip(1)),
+        Some("stats") => return commands::stats::run(args.skip(1)),
+        Some("query") => return commands::query::run(args.skip(1)),
+        Some("set-status") => return commands::status::run(args.skip(1), None),
+        Some("done") => return commands::status::run(args.skip(1), Some(1)),
+        Some("reopen") => return commands::status::run(args.skip(1), Some(0)),
+        Some("history") => return commands::history::run(args.skip(1)),
+        Some("burndown") => return commands::burndown::run(args.skip(1)),
+        Some("add") => return commands::edit::run_add(args.skip(1)),
+        Some("move") => return commands::edit::run_move(args.skip(1)),
+        Some("delete") => return commands::edit::run_delete(args.skip(1)),
+        Some("objective") => return commands::objective::run(args.skip(1)),
+        Some("roadmap") => return commands::roadmap::run(args.skip(1)),
+        Some("changelog") => return commands::changelog::run(args.skip(1)),
+        Some("git") => return commands::git::run(args.skip(1)),
+        Some("trace") => return commands::trace::run(args.skip(1)),
+        Some("config") => return commands::config::run(args.skip(1)),
+        Some("-p") => return commands::project::run(args.skip(1)),
+        _ => ()
+    }
+
+    let Some(options) = SyncOptions::parse(args, Config::get())? else {
+        return Ok(());
+    };
+    if options.watch {
+        return commands::watch::run(&options);
+    }
+    return sync(&options, &mut Vec::new());
+}
+
+impl SyncOptions {
+    /// Flags override the defaults in $config. None if the help menu was printed instead.
+    fn parse(args: impl Iterator<Item = String>, config: &Config) -> Result<Option<SyncOptions>, ()> {
+        let mut input_path: Option<PathBuf> = None;
+        let mut spreadsheet_path: Option<PathBuf> = None;
+        let (format, use_markdown_output) = config.sync_format();
+        let mut options = SyncOptions {
+            input_path: PathBuf::new(),
+            spreadsheet_path: None,
+            output_path: None,
+            overwrite_original_file: config.overwrite,
+            do_dry_run: false,
+            be_verbose: false,
+            use_markdown_output,
+            format,
+            report_format: None,
+            use_sheet_per_category: false,
+            rules: MergeRules::default(),
+            use_newest: false,
+            watch: false,
+            check_git: false,
+            force: false,
+            test_results: Vec::new(),
+            test_sources: Vec::new(),
+            filter: None,
+        };
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            // Interpret first non-opt arg as the input file, and the second as the spreadsheet.
+            // "-" is not an opt, it is stdin.
+            if arg == "-" || !arg.starts_with("-") {
+                let path = PathBuf::from(arg);
+                if input_path.is_none() {
+                    if !is_std_stream(&path) && !path.is_file() {
+                        printerror!("Input file {path:?} does not exist.");
+                        return Err(());
+                    }
+                    input_path = Some(path);
+                } else if spreadsheet_path.is_none() {
+                    spreadsheet_path = Some(path);
+                } else {
+                    printerror!("Unexpected argument {path:?}.");
+                    return Err(());
+                }
+                continue;
+            }
+            match arg.as_str() {
+                "-o" | "--output" => {
+                    let arg = args.next().unwrap_or("".to_string());
+                    if arg.is_empty() || (arg.starts_with("-") && arg != "-") {
+                        printerror!("-o/--open arg must be accompanied with a file path.");
+                        return Err(());
+                    }
+                    options.output_path = Some(PathBuf::from(arg));
+                },
+                "--status-from" | "--contents-from" => {
+                    let authority = match Authority::parse(&args.next().unwrap_or("".to_string())) {
+                        Some(authority) => authority,
+                        None => {
+                            printerror!("{arg} must be accompanied with one of: list, sheet, newest.");
+                            return Err(());
+                        }
+                    };
+                    options.use_newest |= authority == Authority::Newest;
+                    if arg == "--status-from" {
+                        options.rules.status_from = authority;
+                    } else {
+                        options.rules.contents_from = authority;
+                    }
+                },
+                "-f" | "--format" => {
+                    let format = match args.next().unwrap_or("".to_string()).as_str() {
+                        "text" => Some(Format::Text),
+                        "json" => Some(Format::Json),
+                        "yaml" => Some(Format::Yaml),
+                        "toml" => Some(Format::Toml),
+                        _ => {
+                            printerror!("-f/--format must be accompanied with one of: text, json, yaml, toml.");
+                            return Err(());
+                        }
+                    };
+                    // -f and -m override each other and odysseus.toml's format. The last one given wins.
+                    options.format = format;
+                    options.report_format = format;
+                    options.use_markdown_output = false;
+                },
+                "-m" | "--markdown" => {
+                    options.use_markdown_output = true;
+                    options.format = None;
+                },
+                "-s" | "--sheet-per-category" => options.use_sheet_per_category = true,
+                "-w" | "--no-overwrite" => options.overwrite_original_file = false,
+                "--overwrite" => options.overwrite_original_file = true,
+                "-n" | "--dry-run" => options.do_dry_run = true,
+                "-v" | "--verbose" => options.be_verbose = true,
+                "--watch" => options.watch = true,
+                "--git" => options.check_git = true,
+                "--force" => options.force = true,
+                "--where" => options.filter = Some(Filter::parse(&args.next().unwrap_or("".to_string())).ok_or(())?),
+                "--test-results" | "--test-sources" => {
+                    let Some(path) = args.next().map(PathBuf::from) else {
+                        printerror!("{arg} must be accompanied with a path.");
+                        return Err(());
+                    };
+                    if arg == "--test-results" {
+                        options.test_results.push(path);
+                    } else {
+                        options.test_sources.push(path);
+                    }
+                },
+                "-h" | "--help" | _ => {
+                    print_help();
+                    return Ok(None);
+                },
 
-    while let Some(arg) = args.next() {
-        // Interpret first non-opt arg as the input file.
-        if !arg.starts_with("-") {
-            let path = PathBuf::from(arg);
-            if !path.is_file() {
-                printerror!("Input file {path:?} does not exist.");
+            }
+        }
+
+        // Ensure user has provided a input file.
+        options.input_path = match input_path {
+            Some(path) => path,
+            None => {
+                printerror!("User must provide an input path.");
+                return Err(());
+            }
+        };
+        if let Some(path) = &spreadsheet_path {
+            if is_std_stream(&options.input_path) && is_std_stream(path) {
+                printerror!("Requirements file and spreadsheet cannot both be read from stdin.");
                 return Err(());
             }
-            input_path = Some(path);
-            break;
         }
-        match arg.as_str() {
-            "-o" | "--output" => {
-                let arg = args.next().unwrap_or("".to_string());
-                if arg.is_empty() || arg.starts_with("-") {
-                    printerror!("-o/--open arg must be accompanied with a file path.");
-                    return Err(());
-                }
-                output_path = Some(PathBuf::from(arg));
-            },
-            "-m" | "--markdown" => use_markdown_output = true,
-            "-w" | "--no-overwrite" => overwrite_original_file = false,
-            "-n" | "--dry-run" => do_dry_run = true,
-            "-v" | "--verbose" => be_verbose = true,
-            "-h" | "--help" | _ => {
-                print_help();
-                return Ok(());
-            },
+        options.spreadsheet_path = spreadsheet_path;
+
+        // A filtered spreadsheet is missing requirements, which a later sync would take as deleted.
+        if options.filter.is_some() {
+            if options.output_path.is_none() || options.output_path == options.spreadsheet_path {
+                printerror!("--where only exports, it needs an -o path other than the spreadsheet.");
+                return Err(());
+            }
+            options.overwrite_original_file = false;
+        }
 
+        // A document written to a csv (or other) path can't be read by the next sync.
+        let output_path = options.output_path();
+        let extensions = options.format.as_ref().map(|x| x.document_extensions()).unwrap_or_default();
+        let extension = output_path.extension().and_then(|x| x.to_str()).unwrap_or_default();
+        if !options.do_dry_run && !extensions.is_empty() && !is_std_stream(&output_path) && !extensions.contains(&extension) {
+            printerror!("Cannot write {} to {output_path:?}, the next sync would read it as another format. Use a .{} spreadsheet.", extensions[0], extensions[0]);
+            return Err(());
         }
+        return Ok(Some(options));
     }
 
-    // Ensure user has provided a input file.
-    if input_path.is_none() {
-        printerror!("User must provide an input path.");
-        return Err(());
+    /**
+     * -o path if given, otherwise the spreadsheet. If neither was provided, output to ./<input_file_name>.csv,
+     * or .json/.yaml/.toml for those formats. If the list was piped in, pipe the spreadsheet out.
+     */
+    fn output_path(&self) -> PathBuf {
+        return match self.output_path.as_ref().or(self.spreadsheet_path.as_ref()) {
+            Some(path) => path.clone(),
+            None if is_std_stream(&self.input_path) => PathBuf::from("-"),
+            None => default_spreadsheet_path(&self.input_path, self.format.as_ref(), self.use_markdown_output),
+        };
     }
-    let input_path = input_path.unwrap();
+}
+
+/// Translate the requirements file into the spreadsheet, merging in changes made to the spreadsheet.
+/// Each file written is added to $written with its contents as soon as it is written, so a failed sync still tells what it changed.
+fn sync(options: &SyncOptions, written: &mut Vec<(PathBuf, Vec<u8>)>) -> Result<(), ()> {
+    let SyncOptions { ref input_path, do_dry_run, be_verbose, use_markdown_output, use_sheet_per_category, .. } = *options;
+    let format = options.format.as_ref();
+    let report_format = options.report_format.as_ref();
+    let mut rules = options.rules.clone();
+    let mut output_data: HashMap<String, Requirement> = HashMap::new();
+
+    let read_from_stdin = is_std_stream(input_path);
+    let input_data: Vec<Requirement>;
     let categories: HashMap<String, String>;
-    (input_data, categories) = match parse_requirements(&input_path, be_verbose) {
+    (input_data, categories) = match if read_from_stdin {
+        parse_requirements_reader(stdin().lock(), be_verbose)
+    } else {
+        parse_requirements(input_path, be_verbose)
+    } {
         Some(data) => data,
         None => return Err(())
     };
 
-    // If there is one more arg, treat it as the output_data.
-    if args.peek().is_some() {
-        let arg = args.next().unwrap();
-        let path = PathBuf::from(arg);
+    let output_path = options.output_path();
+    // Keep stdout clean when it is being used as the spreadsheet or for json.
+    let write_to_stdout = is_std_stream(&output_path);
+    let be_quiet = write_to_stdout || (do_dry_run && matches!(report_format, Some(Format::Json) | Some(Format::Yaml)));
 
-        // If user did not override output path, use provided spreadsheet path.
-        if output_path.is_none() {
-            output_path = Some(path);
-        }
+    // Read previous spreadsheet. -o path is only read if it already exists.
+    let spreadsheet_path = match &options.spreadsheet_path {
+        Some(path) => Some(path.clone()),
+        None if !write_to_stdout && output_path.is_file() && options.filter.is_none() => Some(output_path.clone()),
+        None => None,
+    };
+    // Snapshot of the spreadsheet at the time of the last sync, used for three way merging.
+    let mut base_data: Option<HashMap<String, Requirement>> = None;
+    match &spreadsheet_path {
+        Some(path) => {
+            output_data = match if is_std_stream(path) {
+                parse_spreadsheet_reader(stdin().lock(), be_verbose)
+            } else {
+                parse_spreadsheet(path, be_verbose)
+            } {
+                Some(data) => data,
+                None => return Err(())
+            };
+            printinfo!(!be_quiet, "Previous csv file provided. Reading from {path:?}.");
+            // Objectives are kept as they are, but are reported so they can be fixed.
+            project::check_objectives(output_data.values());
+
+            let snapshot = snapshot_path(path);
+            if !is_std_stream(path) && snapshot.is_file() {
+                printinfo!(be_verbose, "Reading last sync from {snapshot:?}.");
+                base_data = match parse_spreadsheet(&snapshot, be_verbose) {
+                    Some(data) => Some(data),
+                    None => return Err(())
+                };
+            }
+        },
+        None => printinfo!(!be_quiet, "No previous csv file provided."),
     }
 
+    if options.use_newest {
+        rules.list_is_newer = list_is_newer(input_path, spreadsheet_path.as_ref());
+        printinfo!(be_verbose, "Newest file: {}", if rules.list_is_newer { "list" } else { "spreadsheet" });
+    }
 
-    // If user did not provide a -o arg or a spreadsheet file, output to ./<input_file_name>.csv.
-    let output_path = if output_path.is_none() {
-        printinfo!("No previous csv file provided.");
-        PathBuf::from(input_path.clone().parent().unwrap_or(PathBuf::from(".").as_path())
-            .file_stem()
-            .unwrap_or(&OsString::from("requirements")))
-            .with_extension(
-                if use_markdown_output {
-                    "csv.md"
-                } else {
-                    "csv"
-                })
-    } else {
-        let o = output_path.unwrap();
-        output_data = match parse_spreadsheet(&o, be_verbose) {
-            Some(data) => data,
-            None => return Err(())
-        };
-        printinfo!("Previous csv file provided. Reading from {o:?}.");
-        o
+    printinfo!(!be_quiet, "Translating {input_path:?} -> {output_path:?}");
+    let (mut input_data, conflicts) = merge_requirements(input_data, &output_data, base_data.as_ref(), &rules, be_verbose);
+    for conflict in &conflicts {
+        printerror!("{conflict}");
+    }
 
-    };
-    println!("{output_data:?}");
+    // Test results are applied on top of the merge, and recorded in the history as changes of their own.
+    let merged_data = if options.test_results.is_empty() { None } else { Some(input_data.clone()) };
+    let test_changes = apply_tests(options, &mut input_data, be_quiet)?;
 
-    // Writer to spreadsheet file.
-    let mut spreadsheet_writer = BufWriter::new(match File::create(&output_path) {
-        Ok(writer) => writer,
-        Err(err) => {
-            printerror!("Could not open output file. {err}.");
-            return Err(());
-        }
-    });
+    if let Some(filter) = &options.filter {
+        input_data.retain(|x| filter.matches(x));
+        output_data.retain(|_, x| filter.matches(x));
+    }
 
-    printinfo!("Translating {input_path:?} -> {output_path:?}");
     if do_dry_run {
-        dry_run(input_data, output_data);
+        let diffs = diff_requirements(&output_data, &input_data);
+        let output = match report_format {
+            Some(Format::Json) => RequirementDiff::to_json_format(&diffs),
+            Some(Format::Yaml) => RequirementDiff::to_yaml_format(&diffs),
+            _ => Some(RequirementDiff::to_report(&diffs, stdout().is_terminal())),
+        };
+        print!("{}", output.ok_or(())?);
         return Ok(());
     }
 
     let mut overwritten_input_data: Vec<String> = Vec::with_capacity(input_data.len());
-
-    // Add header to csv file.
-    let output = if use_markdown_output {
-        printinfo!(be_verbose, "Using markdown style header.");
-        Requirement::get_md_header()
-    } else {
-        printinfo!(be_verbose, "Using csv style header.");
-        Requirement::get_csv_header()
-    };
-    let res = spreadsheet_writer.write(&output
-        .chars()
-        .map(|x| x as u8)
-        .collect::<Vec<u8>>());
-    if let Err(err) = res {
-        printerror!("Error while writing spreadsheet header. {err}");
-    }
-
+    let mut snapshot_data: Vec<String> = Vec::with_capacity(input_data.len());
     let mut category = String::new();
 
-    printinfo!(be_verbose, "\nWriting to {output_path:?}");
-
-    // Iterate over input data.
-    // If $key exists in both input and output file, update status.
-    for mut req in input_data {
-        printinfo!(be_verbose, "READ TXT: {}", req.to_text_format());
-
-        // Update status info, if data was find in spreadsheet.
-        if let Some(val) = output_data.get(&req.hash) {
-            printinfo!(be_verbose, "COMPARE TO CSV: {}", val.to_csv_format().trim_end());
-            // If csv is provided, it is assumed to take authority over txt.
-            req.copy_status(&val, be_verbose);
-        }
+    // Iterate over merged data.
+    for req in &input_data {
         if *req.category != category {
             category = req.category.to_string();
             let long_cat = match categories.get(&category) {
@@ -174,29 +382,47 @@ fn main() -> Result<(), ()>{
 
         // Save updated and reformatted data to overwrite input file later.
         overwritten_input_data.push(req.to_text_format());
-        printinfo!(be_verbose, "WRITE TXT -> CSV: {} ", req.to_csv_format());
+        snapshot_data.push(req.to_csv_format());
+    }
 
-        let output = if use_markdown_output {
-            req.to_md_format()
-        } else {
-            req.to_csv_format()
-        };
-        let res = spreadsheet_writer.write(&output
-            .chars()
-            .map(|x| x as u8)
-            .collect::<Vec<u8>>());
-        if let Err(err) = res {
-            printerror!("Error while writing spreadsheet. {err}");
+    if options.check_git && !options.force {
+        if !write_to_stdout {
+            check_uncommitted(&output_path, be_verbose)?;
+        }
+        if options.overwrite_original_file && !read_from_stdin {
+            check_uncommitted(input_path, be_verbose)?;
         }
     }
-    if let Err(err) = spreadsheet_writer.flush() {
-        printerror!("Error while writing spreadsheet. {err}");
+
+    // Status changes since the last sync. Timestamps are derived from the full history.
+    let history = history_path(&output_path);
+    let mut changes: Vec<StatusChange> = Vec::new();
+    if !write_to_stdout && options.filter.is_none() {
+        let mut entries = read_history(&history).ok_or(())?;
+        changes = detect_status_changes(merged_data.as_deref().unwrap_or(&input_data), &output_data, base_data.as_ref(), &entries, Local::now().fixed_offset());
+        changes.extend(test_changes);
+        entries.extend(changes.iter().cloned());
+        apply_history(&mut input_data, &entries);
+    }
+
+    printinfo!(be_verbose, "\nWriting to {output_path:?}");
+    let contents = write_spreadsheet(&output_path, &input_data, &categories, format, use_markdown_output, use_sheet_per_category, be_verbose)?;
+    written.push((output_path.clone(), contents));
+    if !changes.is_empty() {
+        printinfo!(be_verbose, "\nAdding {} status change(s) to {history:?}", changes.len());
+        append_history(&history, &changes).ok_or(())?;
+    }
+
+    // There is no file to overwrite if the list was piped in. Without a rewritten list there is no snapshot
+    // either, as the list still has its old values, and the next sync would take them for list edits.
+    if !options.overwrite_original_file || read_from_stdin {
+        return Ok(());
     }
 
     printinfo!(be_verbose, "\nOverwriting {input_path:?}");
 
     // Writer to original requirements file.
-    let mut requirements_writer = BufWriter::new(match File::create(&input_path) {
+    let mut requirements_writer = BufWriter::new(match File::create(input_path) {
         Ok(writer) => writer,
         Err(err) => {
             printerror!("Could not open input file. {err}.");
@@ -204,44 +430,214 @@ fn main() -> Result<(), ()>{
         }
     });
 
-    if overwrite_original_file {
-        let _ = requirements_writer.write_all(&overwritten_input_data
-            .join("\n")
-            .chars()
-            .map(|x| x as u8)
-            .collect::<Vec<u8>>());
-        if let Err(err) = requirements_writer.flush() {
-            printerror!("Error while overwriting requirements file. {err}");
+    // Toml requirements are rewritten as toml.
+    let output = if is_toml(input_path) {
+        to_toml_format(&input_data, &categories).ok_or(())?
+    } else {
+        overwritten_input_data.join("\n")
+    };
+    if let Err(err) = requirements_writer.write_all(output.as_bytes()).and_then(|_| requirements_writer.flush()) {
+        printerror!("Error while overwriting requirements file. {err}");
+        return Err(());
+    }
+    written.push((input_path.clone(), output.into_bytes()));
+
+    // Remember what was synced, so the next run can tell which side changed.
+    if !write_to_stdout {
+        let snapshot = snapshot_path(&output_path);
+        printinfo!(be_verbose, "\nWriting last sync to {snapshot:?}");
+        let contents = Requirement::get_csv_header().to_string() + &snapshot_data.join("");
+        if let Err(err) = std::fs::write(&snapshot, contents) {
+            printerror!("Could not write snapshot file. {err}");
+            // An old snapshot would be taken for this sync by the next three way merge.
+            let _ = std::fs::remove_file(&snapshot);
+            return Err(());
         }
     }
 
     return Ok(());
 }
 
-fn print_help() {
-    println!("Takes a text file containing a list of requirements and translates them into a spreadsheet.");
-    println!("ody [options] requirements_file [spreadsheet]");
-    println!("If [spreadsheet] is a valid csv file, it is treated as a previous version and odysseus will attempt to preserve its data.");
-    println!("ody will auto generate a unique id for each requirement, by taking a hash of its text contents. This id will be appended to each list item wrapped in '(@<hash>)'. However, if odysseus finds a value of this form in the input file, it will use that instead.");
-    println!("\n\nOptions:");
-    println!("-h | --help\t\tShow this menu.\n-o | --output path\tWrite spreadsheet to $path.\n-w | --no-overwrite\tDon't overwrite original requirements file.\n-n | --dry-run\t\tRun command without writing to fs.\n-m | --markdown\t\tSave output as markdown style table instead of csv.");
+/// Set the status of requirements from --test-results. Returns the status changes made.
+fn apply_tests(options: &SyncOptions, reqs: &mut [Requirement], be_quiet: bool) -> Result<Vec<StatusChange>, ()> {
+    let mut results = Vec::new();
+    for path in &options.test_results {
+        printinfo!(options.be_verbose, "Reading test results from {path:?}.");
+        results.extend(read_test_results(path).ok_or(())?);
+    }
+    let mut function_tags: HashMap<String, Vec<String>> = HashMap::new();
+    for dir in &options.test_sources {
+        printinfo!(options.be_verbose, "Scanning {dir:?} for tagged tests.");
+        for (function, mut targets) in scan_function_tags(dir).ok_or(())? {
+            function_tags.entry(function).or_default().append(&mut targets);
+        }
+    }
+
+    let (changes, unknown) = apply_test_results(reqs, &results, &function_tags, Local::now().fixed_offset());
+    for message in &unknown {
+        printerror!("{message}");
+    }
+    if !options.test_results.is_empty() {
+        printinfo!(!be_quiet, "Read {} test result(s). Changed the status of {} requirement(s).", results.len(), changes.len());
+    }
+    return Ok(changes);
 }
 
-fn dry_run(input_data: Vec<Requirement>, output_data: HashMap<String, Requirement>) {
-    println!();
-    for mut req in input_data {
-        println!("READ TXT: {}", req.to_text_format());
+/// "-" is used in place of a path to read from stdin/write to stdout.
+fn is_std_stream(path: &PathBuf) -> bool {
+    return path.as_os_str() == "-";
+}
+
+/// The spreadsheet in odysseus.toml, or ./<input_file_dir_name>.csv (.csv.md, .json, .yaml or .toml). Used when no spreadsheet is given.
+fn default_spreadsheet_path(input_path: &PathBuf, format: Option<&Format>, use_markdown_output: bool) -> PathBuf {
+    if let Some(path) = &Config::get().spreadsheet {
+        return path.clone();
+    }
+    return PathBuf::from(input_path.parent().unwrap_or(PathBuf::from(".").as_path())
+        .file_stem()
+        .unwrap_or(&OsString::from("requirements")))
+        .with_extension(match format.map(|x| x.document_extensions()) {
+            Some([extension, ..]) => extension,
+            _ if use_markdown_output => "csv.md",
+            _ => "csv",
+        });
+}
 
-        // Update status info, if data was find in spreadsheet.
-        if let Some(val) = output_data.get(&req.hash) {
-            println!("COMPARE TO CSV: {}", val.to_csv_format().trim_end());
-            println!("EDIT STATUS: {} -> {}", req.status, val.status);
-            req.status = val.status;
+/// Returns the contents written.
+fn write_spreadsheet(path: &PathBuf, reqs: &[Requirement], categories: &HashMap<String, String>, format: Option<&Format>, use_markdown_output: bool, use_sheet_per_category: bool, be_verbose: bool) -> Result<Vec<u8>, ()> {
+    // Json/yaml/toml are used if requested, or if path has that extension.
+    let format = match format {
+        Some(Format::Text) | None => match path.extension().and_then(|x| x.to_str()) {
+            Some("json") => Some(&Format::Json),
+            Some("yaml") | Some("yml") => Some(&Format::Yaml),
+            Some("toml") => Some(&Format::Toml),
+            _ => format
+        },
+        format => format
+    };
+    let output = match format {
+        Some(Format::Json) => Some(RequirementSet::new(reqs, categories).to_json()),
+        Some(Format::Yaml) => Some(RequirementSet::new(reqs, categories).to_yaml()),
+        Some(Format::Toml) => Some(to_toml_format(reqs, categories)),
+        _ => None
+    };
+    if let Some(output) = output {
+        let output = output.ok_or(())?;
+        let mut writer = open_writer(path).ok_or(())?;
+        if let Err(err) = writer.write_all(output.as_bytes()).and_then(|_| writer.flush()) {
+            printerror!("Error while writing spreadsheet. {err}");
+            return Err(());
         }
-        println!("WRITE TXT -> CSV: {} ", req.to_csv_format());
+        return Ok(output.into_bytes());
+    }
+    if is_workbook(path) {
+        printinfo!(be_verbose, "Using {} workbook.", if use_sheet_per_category { "one sheet per category" } else { "single sheet" });
+        write_workbook(path, reqs, use_sheet_per_category).ok_or(())?;
+        // Workbooks are built by their library, so their contents are only known once written.
+        return Ok(std::fs::read(path).unwrap_or_default());
+    }
+
+    // Writer to spreadsheet file.
+    let mut spreadsheet_writer = match open_writer(path) {
+        Some(writer) => writer,
+        None => return Err(())
+    };
+
+    // Add header to csv file.
+    let mut output = if use_markdown_output {
+        printinfo!(be_verbose, "Using markdown style header.");
+        Requirement::get_md_header()
+    } else {
+        printinfo!(be_verbose, "Using csv style header.");
+        Requirement::get_csv_header()
+    }.to_string();
+
+    for req in reqs {
+        printinfo!(be_verbose, "WRITE TXT -> CSV: {} ", req.to_csv_format());
+
+        output += &if use_markdown_output {
+            req.to_md_format()
+        } else {
+            req.to_csv_format()
+        };
+    }
+    if let Err(err) = spreadsheet_writer.write_all(output.as_bytes()).and_then(|_| spreadsheet_writer.flush()) {
+        printerror!("Error while writing spreadsheet. {err}");
+        return Err(());
+    }
+    return Ok(output.into_bytes());
+}
+
+/// Whether the list was modified after the spreadsheet. Piped input is treated as brand new.
+fn list_is_newer(list: &PathBuf, spreadsheet: Option<&PathBuf>) -> bool {
+    let spreadsheet = match spreadsheet {
+        Some(path) => path,
+        None => return true
+    };
+    if is_std_stream(list) {
+        return true;
     }
+    if is_std_stream(spreadsheet) {
+        return false;
+    }
+    let modified = |path: &PathBuf| std::fs::metadata(path).and_then(|x| x.modified()).ok();
+    return modified(list) > modified(spreadsheet);
 }
 
+/// Refuse to overwrite a file with changes git hasn't committed. Files outside a repository are always overwritten.
+fn check_uncommitted(path: &PathBuf, be_verbose: bool) -> Result<(), ()> {
+    if !path.is_file() || !git::is_in_repository(path) {
+        printinfo!(be_verbose, "{path:?} is not in a git repository, so it is overwritten.");
+        return Ok(());
+    }
+    return match git::has_uncommitted_changes(path) {
+        Some(false) => Ok(()),
+        Some(true) => {
+            printerror!("{path:?} has uncommitted changes. Commit them first, or use --force to overwrite it anyway.");
+            Err(())
+        },
+        None => Err(()),
+    };
+}
+
+fn open_writer(path: &PathBuf) -> Option<Box<dyn Write>> {
+    if is_std_stream(path) {
+        return Some(Box::new(BufWriter::new(stdout())));
+    }
+    return match File::create(path) {
+        Ok(file) => Some(Box::new(BufWriter::new(file))),
+        Err(err) => {
+            printerror!("Could not open output file. {err}.");
+            None
+        }
+    };
+}
+
+fn print_help() {
+    println!("Takes a text file containing a list of requirements and translates them into a spreadsheet.");
+    println!("ody [options] requirements_file [spreadsheet]");
+    println!("Use '-' as requirements_file, spreadsheet or -o path to read from stdin/write to stdout.");
+    println!("If [spreadsheet] is a valid csv file, it is treated as a previous version and odysseus will attempt to preserve its data.");
+    println!("ody will auto generate a unique id for each requirement, by taking a hash of its text contents. This id will be appended to each list item wrapped in '(@<hash>)'. However, if odysseus finds a value of this form in the input file, it will use that instead.");
+    println!("\nCommands:");
+    println!("report\t\t\tWrite an html progress report. See ody report --help.");
+    println!("stats\t\t\tShow completion per category, requirement and objective. See ody stats --help.");
+    println!("query\t\t\tPrint requirements matching a filter. See ody query --help.");
+    println!("set-status\t\tChange the status of a requirement in place. Also: done, reopen. See ody set-status --help.");
+    println!("history\t\t\tShow the recorded status changes of a requirement. See ody history --help.");
+    println!("burndown\t\tShow open and done requirements per day, as a chart, csv or json. See ody burndown --help.");
+    println!("add/move/delete\t\tAdd, move or delete requirements. See ody add --help.");
+    println!("objective\t\tManage a project's objectives. See ody objective --help.");
+    println!("roadmap\t\t\tShow a project's objectives and what is left to do. See ody roadmap --help.");
+    println!("changelog\t\tWrite changelog sections for completed objectives. See ody changelog --help.");
+    println!("git blame/diff\t\tShow when requirements were introduced and changed, or what changed between commits. See ody git --help.");
+    println!("trace\t\t\tFind requirements referenced in source code, and which have no implementation or tests. See ody trace --help.");
+    println!("config show\t\tShow the defaults read from odysseus.toml, and where each came from. See ody config --help.");
+    println!("-p open\t\t\tOpen a project's environment. See ody -p --help.");
+    println!("\n\nOptions:");
+    println!("-h | --help\t\tShow this menu.\n-o | --output path\tWrite spreadsheet to $path.\n-w | --no-overwrite\tDon't overwrite original requirements file.\n--overwrite\t\tOverwrite it, even if odysseus.toml says not to.\n-n | --dry-run\t\tShow what would change in the spreadsheet, without writing to fs.\n-f | --format fmt\tFormat of the spreadsheet: text (default), json, yaml or toml. With -n, format of the report instead: text, json or yaml.\n-m | --markdown\t\tSave output as markdown style table instead of csv.\n-s | --sheet-per-category\tWhen writing .xlsx/.ods, put each category on its own sheet.");
+    println!("--status-from src\tWhich file's status is kept when they differ: list, sheet (default) or newest.\n--contents-from src\tWhich file's contents are kept when they differ: list (default), sheet or newest.\n--watch\t\t\tKeep running, and sync again whenever the requirements file or spreadsheet is saved.\n--git\t\t\tDon't overwrite files with uncommitted git changes.\n--force\t\t\tOverwrite them anyway.\n--test-results path\tMark requirements done or failed from a JUnit XML or cargo test json file. Can be repeated.\n--test-sources dir\tFind tags in the comments above tests in $dir, for --test-results. Can be repeated.\n--where filter\t\tOnly write requirements matching $filter to the -o path, without touching the requirements file or spreadsheet. See ody query --help.");
+}
 
 #[cfg(test)]
 mod tests {
@@ -255,8 +651,81 @@ mod tests {
 
         let input_data = parse_requirements(&input_path, true).unwrap().0;
         let output_data = parse_spreadsheet(&output_path, true).unwrap();
-        dry_run(input_data, output_data);
-        // assert!(false);
-        assert!(true);
+        let (input_data, conflicts) = merge_requirements(input_data, &output_data, None, &MergeRules::default(), true);
+        let diffs = diff_requirements(&output_data, &input_data);
+
+        // Spreadsheet already agrees with the list.
+        assert!(conflicts.is_empty());
+        assert!(diffs.is_empty());
+    }
+    #[test]
+    fn document_spreadsheet_path() {
+        let parse = |args: &[&str]| SyncOptions::parse(args.iter().map(|x| x.to_string()), &Config::default());
+        assert_eq!(parse(&["-f", "json", "tests/test.txt"]).unwrap().unwrap().output_path(), PathBuf::from("tests.json"));
+        assert_eq!(parse(&["-f", "yaml", "tests/test.txt", "-o", "out.yml"]).unwrap().unwrap().output_path(), PathBuf::from("out.yml"));
+        assert_eq!(parse(&["-m", "tests/test.txt"]).unwrap().unwrap().output_path(), PathBuf::from("tests.csv.md"));
+        assert!(parse(&["-f", "toml", "tests/test.txt", "-o", "out.csv"]).is_err());
+        assert!(parse(&["-f", "json", "tests/test.txt", "tests/test.csv"]).is_err());
+        // Nothing is written on a dry run, -f is the report's format.
+        assert!(parse(&["-n", "-f", "json", "tests/test.txt", "tests/test.csv"]).unwrap().is_some());
+    }
+    #[test]
+    fn config_format() {
+        let config = Config { format: "json".to_string(), ..Default::default() };
+        let parse = |args: &[&str]| SyncOptions::parse(args.iter().map(|x| x.to_string()), &config).unwrap().unwrap();
+        let options = parse(&["tests/test.txt"]);
+        assert_eq!((options.format, options.output_path()), (Some(Format::Json), PathBuf::from("tests.json")));
+        // The report stays text unless -f is given.
+        assert_eq!(parse(&["-n", "tests/test.txt"]).report_format, None);
+        let options = parse(&["-m", "tests/test.txt"]);
+        assert_eq!((options.format, options.output_path()), (None, PathBuf::from("tests.csv.md")));
+        let options = parse(&["-m", "-f", "yaml", "tests/test.txt"]);
+        assert_eq!((options.use_markdown_output, options.output_path()), (false, PathBuf::from("tests.yaml")));
+    }
+    #[test]
+    fn filtered_export() {
+        let dir = std::env::temp_dir().join(format!("odysseus-filtered-export-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let list = dir.join("reqs.md").display().to_string();
+        let export = dir.join("open.json").display().to_string();
+        let contents = "Category (CAT)\n- [ ] First(@h1)\n- [x] Second(@h2)\n";
+        std::fs::write(&list, contents).unwrap();
+        let parse = |args: &[&str]| SyncOptions::parse(args.iter().map(|x| x.to_string()), &Config::default());
+
+        assert!(parse(&["--where", "status=open", &list]).is_err());
+        let options = parse(&["--where", "status=open", &list, "-o", &export]).unwrap().unwrap();
+        assert!(!options.overwrite_original_file);
+        sync(&options, &mut Vec::new()).unwrap();
+
+        let exported = std::fs::read_to_string(&export).unwrap();
+        assert!(exported.contains("\"h1\"") && !exported.contains("\"h2\""));
+        assert_eq!(std::fs::read_to_string(&list).unwrap(), contents);
+        assert!(!history_path(&PathBuf::from(&export)).exists() && !snapshot_path(&PathBuf::from(&export)).exists());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+    #[test]
+    fn sheet_edits_survive_no_overwrite() {
+        let dir = std::env::temp_dir().join(format!("odysseus-no-overwrite-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let list = dir.join("reqs.md").display().to_string();
+        let sheet = dir.join("reqs.csv").display().to_string();
+        let contents = "Category (CAT)\n- [ ] First(@h1)\n- [ ] Second(@h2)\n";
+        std::fs::write(&list, contents).unwrap();
+        let run = |args: &[&str]| sync(&SyncOptions::parse(args.iter().map(|x| x.to_string()), &Config::default()).unwrap().unwrap(), &mut Vec::new()).unwrap();
+
+        run(&["-w", &list, "-o", &sheet]);
+        let edited = std::fs::read_to_string(&sheet).unwrap().replace(",First,0,", ",First,1,");
+        std::fs::write(&sheet, edited).unwrap();
+        // The list never gets the edit, so there is no snapshot the list's value could win against.
+        run(&["-w", &list, &sheet]);
+        run(&["-w", &list, &sheet]);
+
+        let output = parse_spreadsheet(&PathBuf::from(&sheet), false).unwrap();
+        assert_eq!(output["h1"].status, 1);
+        assert_eq!(output["h2"].status, 0);
+        assert_eq!(std::fs::read_to_string(&list).unwrap(), contents);
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use crate::requirements::Requirement;
+use super::{objective_version, Project, Version};
+
+const CHANGELOG_HEADER: &str = "# Changelog\nAll notable changes to this project are documented in this file.\n\nThe format is based on [Keep a Changelog](https://keepachangelog.com/en/1.1.0/), and this project adheres to [Semantic Versioning](https://semver.org/spec/v2.0.0.html).\n";
+
+/**
+ * A Keep a Changelog section for each completed objective, newest first. Requirements are grouped by
+ * category, using the category's long name if it is in $categories (see RequirementSet::categories).
+ */
+pub fn changelog_sections(project: &Project, reqs: &[Requirement], categories: &BTreeMap<String, String>) -> Vec<(Version, String)> {
+    let mut sections: Vec<(Version, String)> = Vec::new();
+    for (version, objective) in project.objectives.iter().rev() {
+        let Some(completed) = objective.completed else { continue };
+        let mut section = format!("## [{version}] - {completed}\n");
+        if !objective.name.is_empty() {
+            section += &format!("{}\n", objective.name);
+        }
+        if !objective.description.is_empty() {
+            section += &format!("\n{}\n", objective.description);
+        }
+
+        let reqs: Vec<&Requirement> = reqs.iter().filter(|x| objective_version(x).as_ref() == Some(version)).collect();
+        let mut order: Vec<&str> = Vec::new();
+        for req in &reqs {
+            if !order.contains(&req.category.as_str()) {
+                order.push(&req.category);
+            }
+        }
+        for category in order {
+            section += &format!("\n### {}\n", category_name(category, categories));
+            for req in reqs.iter().filter(|x| *x.category == category) {
+                section += &format!("- {}\n", req.contents);
+            }
+        }
+        sections.push((version.clone(), section));
+    }
+    return sections;
+}
+
+/// "Requirements Translator (REQT)" -> "Requirements Translator"
+fn category_name<'a>(category: &'a str, categories: &'a BTreeMap<String, String>) -> &'a str {
+    let Some(header) = categories.get(category) else { return category };
+    let name = header.trim().strip_suffix(&format!("({category})")).unwrap_or(header).trim();
+    return if name.is_empty() { category } else { name };
+}
+
+/**
+ * Add sections to an existing changelog, skipping any version it already has. New sections go above
+ * the newest release, below the title and an [Unreleased] section. Returns the changelog and how many sections were added.
+ */
+pub fn prepend_changelog(changelog: &str, sections: &[(Version, String)]) -> (String, usize) {
+    let changelog = if changelog.trim().is_empty() { CHANGELOG_HEADER } else { changelog };
+    let is_release = |line: &str| line.starts_with("## ") && !line.to_lowercase().contains("unreleased");
+    let released: Vec<Version> = changelog.lines()
+        .filter(|x| is_release(x))
+        .filter_map(|x| x[3..].trim().trim_start_matches('[').split([']', ' ']).next()?.parse().ok())
+        .collect();
+    let new: Vec<&String> = sections.iter().filter(|(version, _)| !released.contains(version)).map(|(_, x)| x).collect();
+    if new.is_empty() {
+        return (changelog.to_string(), 0);
+    }
+
+    let mut lines = changelog.lines().peekable();
+    let mut output = String::new();
+    while let Some(line) = lines.next_if(|x| !is_release(x)) {
+        output += line;
+        output += "\n";
+    }
+    if !output.ends_with("\n\n") {
+        output += "\n";
+    }
+    for section in &new {
+        output += section;
+        output += "\n";
+    }
+    for line in lines {
+        output += line;
+        output += "\n";
+    }
+    // No releases followed the new sections.
+    if output.ends_with("\n\n") {
+        output.pop();
+    }
+    return (output, new.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::NaiveDate;
+
+    use super::*;
+    use crate::project::Objective;
+
+    fn sections() -> Vec<(Version, String)> {
+        let mut project = Project::default();
+        for (version, completed) in [("0.1.0", Some(1)), ("0.2.0", Some(10)), ("0.3.0", None)] {
+            project.objectives.insert(version.parse().unwrap(), Objective {
+                name: format!("Objective {version}"),
+                completed: completed.and_then(|x| NaiveDate::from_ymd_opt(2026, 10, x)),
+                ..Default::default()
+            });
+        }
+        let reqs = vec![
+            Requirement::test("Parse lists.", 1).in_category("REQT").with_contents("Parse lists.").with_objective("0.1.0"),
+            Requirement::test("Write readme.", 1).in_category("ADMN").with_contents("Write readme.").with_objective("0.2.0"),
+            Requirement::test("Status updates.", 1).in_category("REQT").with_contents("Status updates.").with_objective("0.2.0"),
+            Requirement::test("Later.", 1).in_category("REQT").with_contents("Later.").with_objective("0.3.0"),
+        ];
+        let categories = BTreeMap::from([("REQT".to_string(), "Requirements Translator (REQT)".to_string())]);
+        return changelog_sections(&project, &reqs, &categories);
+    }
+
+    #[test]
+    fn changelog_format() {
+        let sections = sections();
+        assert_eq!(sections.iter().map(|x| x.0.to_string()).collect::<Vec<String>>(), vec!["0.2.0", "0.1.0"]);
+        assert_eq!(sections[0].1, "## [0.2.0] - 2026-10-10\nObjective 0.2.0\n\n### ADMN\n- Write readme.\n\n### Requirements Translator\n- Status updates.\n");
+    }
+    #[test]
+    fn prepend_without_duplicates() {
+        let sections = sections();
+        let existing = "# Changelog\n\n## [Unreleased]\n- Something.\n\n## [0.1.0] - 2026-10-01\nObjective 0.1.0\n";
+        let (changelog, count) = prepend_changelog(existing, &sections);
+        assert_eq!(count, 1);
+        assert_eq!(changelog, format!("# Changelog\n\n## [Unreleased]\n- Something.\n\n{}\n## [0.1.0] - 2026-10-01\nObjective 0.1.0\n", sections[0].1));
+        assert_eq!(prepend_changelog(&changelog, &sections), (changelog.clone(), 0));
+
+        let (changelog, count) = prepend_changelog("", &sections);
+        assert_eq!(count, 2);
+        assert!(changelog.starts_with(CHANGELOG_HEADER));
+        assert!(changelog.ends_with("### Requirements Translator\n- Parse lists.\n"));
+    }
+}
@@ -0,0 +1,393 @@
+use std::{env, fmt::Display, path::{Path, PathBuf}, process::{Command, Stdio}};
+use glob::{glob, Pattern};
+use mythos_core::{printerror, printwarn};
+use serde::{Deserialize, Serialize};
+
+use super::Project;
+
+/**
+ * Programs to open along with a project, defined in the project file's [environment] section.
+ * E.g.
+ *     [environment]
+ *     terminal = "alacritty --title {title} --working-directory {cwd} -e {run}"
+ *
+ *     [[environment.terminals]]
+ *     title = "{project} server"
+ *     cwd = "server"
+ *     run = "cargo run"
+ *
+ *     [[environment.files]]
+ *     glob = "*.pdf"
+ *     program = "zathura {file}"
+ */
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Environment {
+    /// Default command template for terminals.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub terminal: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub terminals: Vec<Terminal>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<FileEntry>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Terminal {
+    /// Overrides the environment's command template.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub command: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub title: String,
+    /// Relative to the project's working directory.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub cwd: String,
+    /// Command to run once the terminal starts.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub run: String,
+}
+
+/// Files to open with a program.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FileEntry {
+    /// Relative to the project's working directory.
+    pub glob: String,
+    /// Command template. {file} opens each file separately, otherwise every file is passed to one program.
+    pub program: String,
+    #[serde(default = "enabled_default", skip_serializing_if = "is_enabled")]
+    pub enabled: bool,
+}
+
+fn enabled_default() -> bool {
+    return true;
+}
+
+fn is_enabled(enabled: &bool) -> bool {
+    return *enabled;
+}
+
+/// A program to start, with its arguments and working directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Launch {
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+}
+
+impl Display for Launch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "(cd {:?} && {}", self.cwd, self.program)?;
+        for arg in &self.args {
+            if arg.is_empty() || arg.contains(char::is_whitespace) {
+                write!(f, " {arg:?}")?;
+            } else {
+                write!(f, " {arg}")?;
+            }
+        }
+        return write!(f, ")");
+    }
+}
+
+/// Starts programs. Kept behind a trait so tests don't open real windows.
+pub trait Spawner {
+    fn spawn(&mut self, launch: &Launch) -> Option<()>;
+}
+
+/// Prints each program instead of starting it.
+pub struct DryRunSpawner;
+
+impl Spawner for DryRunSpawner {
+    fn spawn(&mut self, launch: &Launch) -> Option<()> {
+        println!("{launch}");
+        return Some(());
+    }
+}
+
+/// Starts each program as its own process, without waiting for it to exit.
+pub struct ProcessSpawner;
+
+impl Spawner for ProcessSpawner {
+    fn spawn(&mut self, launch: &Launch) -> Option<()> {
+        let result = Command::new(&launch.program)
+            .args(&launch.args)
+            .current_dir(&launch.cwd)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Err(err) = result {
+            printerror!("Could not start {}. {err}", launch.program);
+            return None;
+        }
+        return Some(());
+    }
+}
+
+impl Environment {
+    pub fn is_empty(&self) -> bool {
+        return self.terminal.is_empty() && self.terminals.is_empty() && self.files.is_empty();
+    }
+}
+
+impl Terminal {
+    /**
+     * Fill in the command template. Environment variables are expanded and the template is split on whitespace,
+     * then {title}, {cwd}, {run} and {project} are replaced within each argument, so values with spaces stay one argument.
+     * The exception is an argument that is just {run}, which is replaced by the command's own arguments (see split_words).
+     * If there is nothing to run, the {run} argument is left out, along with the option before it.
+     * The title defaults to the project's name.
+     */
+    pub fn to_launch(&self, template: &str, project: &Project) -> Option<Launch> {
+        let template = expand_vars(if self.command.is_empty() { template } else { &self.command })?;
+        let cwd = project.working_dir.join(expand_vars(&self.cwd)?);
+        let title = if self.title.is_empty() { &project.name } else { &self.title };
+        let mut args: Vec<String> = Vec::new();
+        for arg in template.split_whitespace() {
+            if arg.contains("{run}") && self.run.is_empty() {
+                if args.last().is_some_and(|x| x.starts_with('-')) {
+                    args.pop();
+                }
+                continue;
+            }
+            if arg == "{run}" {
+                args.extend(split_words(&self.run)?);
+                continue;
+            }
+            args.push(arg
+                .replace("{title}", title)
+                .replace("{cwd}", &cwd.to_string_lossy())
+                .replace("{run}", &self.run)
+                .replace("{project}", &project.name));
+        }
+        if args.is_empty() {
+            printerror!("No command set for terminal \"{}\". Set terminal in [environment] or command for the terminal.", self.title);
+            return None;
+        }
+        let program = args.remove(0);
+        return Some(Launch { program, args, cwd });
+    }
+}
+
+impl FileEntry {
+    /// One launch per file if the program has {file}, otherwise a single launch with every file. Empty if nothing matches.
+    pub fn to_launches(&self, project: &Project) -> Option<Vec<Launch>> {
+        let pattern = expand_vars(&self.glob)?;
+        let pattern = if Path::new(&pattern).is_absolute() {
+            pattern
+        } else {
+            format!("{}/{pattern}", Pattern::escape(&project.working_dir.to_string_lossy()))
+        };
+        let files: Vec<String> = match glob(&pattern) {
+            Ok(paths) => paths.flatten().filter(|x| x.is_file()).map(|x| x.to_string_lossy().to_string()).collect(),
+            Err(err) => {
+                printerror!("Invalid glob \"{}\". {err}", self.glob);
+                return None;
+            }
+        };
+        if files.is_empty() {
+            printwarn!("No files match \"{}\".", self.glob);
+            return Some(Vec::new());
+        }
+
+        let template: Vec<String> = expand_vars(&self.program)?.split_whitespace().map(|x| x.to_string()).collect();
+        if template.is_empty() {
+            printerror!("No program set for \"{}\".", self.glob);
+            return None;
+        }
+        let launch = |args: Vec<String>| Launch { program: template[0].clone(), args, cwd: project.working_dir.clone() };
+        if template.iter().any(|x| x.contains("{file}")) {
+            return Some(files.iter().map(|file| launch(template[1..].iter().map(|x| x.replace("{file}", file)).collect())).collect());
+        }
+        return Some(vec![launch(template[1..].iter().cloned().chain(files).collect())]);
+    }
+}
+
+/// Replace $VAR and ${VAR} with the variable's value, and a leading ~ with $HOME.
+fn expand_vars(value: &str) -> Option<String> {
+    let mut output = String::new();
+    let mut chars = value.chars().peekable();
+    if value == "~" || value.starts_with("~/") {
+        chars.next();
+        output += &env::var("HOME").unwrap_or_default();
+    }
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            output.push(c);
+            continue;
+        }
+        let is_braced = chars.next_if_eq(&'{').is_some();
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|x| x.is_ascii_alphanumeric() || *x == '_') {
+            name.push(c);
+        }
+        if is_braced && chars.next_if_eq(&'}').is_none() {
+            printerror!("Unclosed ${{ in \"{value}\".");
+            return None;
+        }
+        if name.is_empty() {
+            output.push('$');
+            continue;
+        }
+        match env::var(&name) {
+            Ok(var) => output += &var,
+            Err(_) => {
+                printerror!("Environment variable {name} is not set, but is used in \"{value}\".");
+                return None;
+            }
+        }
+    }
+    return Some(output);
+}
+
+/**
+ * Split a command into its arguments the way a shell would, without expanding anything. Whitespace separates
+ * arguments, except within single or double quotes, and a backslash escapes the next character outside single quotes.
+ */
+fn split_words(value: &str) -> Option<Vec<String>> {
+    let mut words: Vec<String> = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some('\''), c) => word.get_or_insert_default().push(c),
+            (_, '\\') => match chars.next() {
+                Some(c) => word.get_or_insert_default().push(c),
+                None => {
+                    printerror!("Trailing \\ in \"{value}\".");
+                    return None;
+                },
+            },
+            (Some(_), c) => word.get_or_insert_default().push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                word.get_or_insert_default();
+            },
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_default().push(c),
+        }
+    }
+    if let Some(quote) = quote {
+        printerror!("Unclosed {quote} in \"{value}\".");
+        return None;
+    }
+    words.extend(word);
+    return Some(words);
+}
+
+/// Every program in the project's environment.
+pub fn environment_launches(project: &Project) -> Option<Vec<Launch>> {
+    let mut launches: Vec<Launch> = Vec::new();
+    for terminal in &project.environment.terminals {
+        let launch = terminal.to_launch(&project.environment.terminal, project)?;
+        if !launch.cwd.is_dir() {
+            printerror!("Working directory {:?} of terminal \"{}\" does not exist.", launch.cwd, terminal.title);
+            return None;
+        }
+        launches.push(launch);
+    }
+    for entry in project.environment.files.iter().filter(|x| x.enabled) {
+        launches.extend(entry.to_launches(project)?);
+    }
+    return Some(launches);
+}
+
+/// Start everything in the project's environment. Returns how many programs were started.
+pub fn open_environment(project: &Project, spawner: &mut dyn Spawner) -> Option<usize> {
+    let launches = environment_launches(project)?;
+    for launch in &launches {
+        spawner.spawn(launch)?;
+    }
+    return Some(launches.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct RecordingSpawner(Vec<Launch>);
+
+    impl Spawner for RecordingSpawner {
+        fn spawn(&mut self, launch: &Launch) -> Option<()> {
+            self.0.push(launch.clone());
+            return Some(());
+        }
+    }
+
+    fn project() -> Project {
+        let contents = "working_dir = \"/tmp\"\n\n[environment]\nterminal = \"term --title {title} --dir {cwd} -e {run}\"\n\n\
+            [[environment.terminals]]\n\n\
+            [[environment.terminals]]\ntitle = \"{project} build\"\ncwd = \".\"\nrun = \"cargo watch -x test\"\n\n\
+            [[environment.terminals]]\ncommand = \"other-term {title}\"\ntitle = \"logs\"\n";
+        let mut project: Project = toml::from_str(contents).unwrap();
+        project.name = "ody".to_string();
+        return project;
+    }
+
+    #[test]
+    fn open_terminals() {
+        let mut spawner = RecordingSpawner::default();
+        assert_eq!(open_environment(&project(), &mut spawner), Some(3));
+        let args: Vec<&Vec<String>> = spawner.0.iter().map(|x| &x.args).collect();
+        assert_eq!(spawner.0[0].program, "term");
+        assert_eq!(args[0], &vec!["--title", "ody", "--dir", "/tmp/"]);
+        assert_eq!(args[1], &vec!["--title", "ody build", "--dir", "/tmp/.", "-e", "cargo", "watch", "-x", "test"]);
+        assert_eq!(spawner.0[2].program, "other-term");
+        assert_eq!(args[2], &vec!["logs"]);
+    }
+    #[test]
+    fn open_files() {
+        let dir = env::temp_dir().join(format!("odysseus-environment-{}", std::process::id()));
+        fs::create_dir_all(dir.join("docs")).unwrap();
+        for file in ["docs/a.pdf", "docs/b.pdf", "notes.md"] {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        env::set_var("ODY_TEST_EDITOR", "edit --wait");
+        let contents = "[[environment.files]]\nglob = \"docs/*.pdf\"\nprogram = \"viewer {file}\"\n\n\
+            [[environment.files]]\nglob = \"*.md\"\nprogram = \"${ODY_TEST_EDITOR}\"\n\n\
+            [[environment.files]]\nglob = \"*.md\"\nprogram = \"disabled\"\nenabled = false\n";
+        let mut project: Project = toml::from_str(contents).unwrap();
+        project.working_dir = dir.clone();
+
+        let mut spawner = RecordingSpawner::default();
+        assert_eq!(open_environment(&project, &mut spawner), Some(3));
+        let _ = fs::remove_dir_all(&dir);
+        let file = |x: &str| dir.join(x).to_string_lossy().to_string();
+        assert_eq!(spawner.0[0].args, vec![file("docs/a.pdf")]);
+        assert_eq!(spawner.0[1].args, vec![file("docs/b.pdf")]);
+        assert_eq!(spawner.0[2].program, "edit");
+        assert_eq!(spawner.0[2].args, vec!["--wait".to_string(), file("notes.md")]);
+        assert!(!toml::to_string(&project).unwrap().contains("enabled = true"));
+    }
+    #[test]
+    fn split_commands() {
+        assert_eq!(split_words("cargo watch  -x test").unwrap(), vec!["cargo", "watch", "-x", "test"]);
+        assert_eq!(split_words(r#"sh -c 'echo "$HOME"; ls' a\ b "" "it's""#).unwrap(), vec!["sh", "-c", "echo \"$HOME\"; ls", "a b", "", "it's"]);
+        assert!(split_words("echo 'unclosed").is_none());
+        assert!(split_words("echo \\").is_none());
+        // Only a whole {run} argument is split.
+        let terminal = Terminal { run: "cargo run".to_string(), ..Default::default() };
+        let launch = terminal.to_launch("term --command={run}", &Project::default()).unwrap();
+        assert_eq!(launch.args, vec!["--command=cargo run"]);
+    }
+    #[test]
+    fn expand_variables() {
+        env::set_var("ODY_TEST_VAR", "value");
+        assert_eq!(expand_vars("a/$ODY_TEST_VAR/${ODY_TEST_VAR}b $ c").as_deref(), Some("a/value/valueb $ c"));
+        assert!(expand_vars("$ODY_TEST_UNSET_VAR").is_none());
+        assert!(expand_vars("${ODY_TEST_VAR").is_none());
+    }
+    #[test]
+    fn invalid_terminals() {
+        let mut project = project();
+        project.environment.terminals[0].cwd = "does-not-exist".to_string();
+        assert!(open_environment(&project, &mut RecordingSpawner::default()).is_none());
+
+        project.environment.terminal = String::new();
+        project.environment.terminals[0].cwd = String::new();
+        assert!(environment_launches(&project).is_none());
+    }
+}
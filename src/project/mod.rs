@@ -0,0 +1,324 @@
+//! Projects managed by odysseus. Each project is defined by $MYTHOS_LOCAL_DATA_DIR/odysseus/<project-name>.toml.
+mod changelog;
+mod environment;
+mod objective;
+mod roadmap;
+mod version;
+
+use std::{collections::BTreeMap, fs, path::PathBuf};
+use chrono::NaiveDate;
+use mythos_core::{dirs::{get_dir, MythosDir}, printerror};
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Table, Value};
+
+use crate::requirements::Requirement;
+
+pub use changelog::{changelog_sections, prepend_changelog};
+pub use environment::{open_environment, DryRunSpawner, Environment, ProcessSpawner, Spawner};
+pub use objective::{assign_objective, check_objectives, objective_version};
+pub use roadmap::Roadmap;
+pub use version::Version;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Project {
+    #[serde(skip)]
+    pub name: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(default)]
+    pub working_dir: PathBuf,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<Version>,
+    /**
+     * By default, the version only advances through objectives that are complete along with every
+     * objective before them. If set, completing 1.1.0 before 1.0.0 moves the version to 1.1.0.
+     */
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub advance_out_of_order: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub objectives: BTreeMap<Version, Objective>,
+    #[serde(default, skip_serializing_if = "Environment::is_empty")]
+    pub environment: Environment,
+    /// Settings odysseus doesn't use are kept as they are.
+    #[serde(flatten)]
+    pub other: toml::Table,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Objective {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// When the objective was completed, and the project reached its version.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<NaiveDate>,
+}
+
+impl Project {
+    /// $MYTHOS_LOCAL_DATA_DIR/odysseus
+    pub fn dir() -> Option<PathBuf> {
+        let dir = get_dir(MythosDir::LocalData, "odysseus");
+        if dir.is_none() {
+            printerror!("Could not find odysseus' data directory.");
+        }
+        return dir;
+    }
+
+    pub fn load(name: &str) -> Option<Project> {
+        let path = Project::dir()?.join(name).with_extension("toml");
+        if !path.is_file() {
+            printerror!("Project {name} does not exist. Expected to find {path:?}.");
+            return None;
+        }
+        return Project::read(&path);
+    }
+
+    pub fn read(path: &PathBuf) -> Option<Project> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                printerror!("Could not open project file {path:?}. {err}");
+                return None;
+            }
+        };
+        let mut project: Project = match toml::from_str(&contents) {
+            Ok(project) => project,
+            Err(err) => {
+                printerror!("Could not parse project file {path:?}. {err}");
+                return None;
+            }
+        };
+        project.name = path.file_stem().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        project.path = path.clone();
+        return Some(project);
+    }
+
+    /**
+     * Load a project by name. If no name is given, use the project whose working directory
+     * contains the current directory.
+     */
+    pub fn find(name: Option<&str>) -> Option<Project> {
+        if let Some(name) = name {
+            return Project::load(name);
+        }
+        let cwd = std::env::current_dir().ok()?;
+        let entries = fs::read_dir(Project::dir()?).map(|x| x.flatten().collect::<Vec<_>>()).unwrap_or_default();
+        let project = entries.into_iter()
+            .map(|x| x.path())
+            .filter(|x| x.extension().is_some_and(|x| x == "toml") && x.file_stem().is_some_and(|x| x != "projects"))
+            .filter_map(|x| toml::from_str::<Project>(&fs::read_to_string(&x).ok()?).ok().map(|project| (x, project)))
+            .filter(|(_, project)| !project.working_dir.as_os_str().is_empty() && cwd.starts_with(&project.working_dir))
+            // Most specific working directory.
+            .max_by_key(|(_, project)| project.working_dir.components().count());
+        return match project {
+            Some((path, _)) => Project::read(&path),
+            None => {
+                printerror!("The current directory is not part of any project. Use --project to pick one.");
+                None
+            }
+        };
+    }
+
+    /// Write the project file. Comments and formatting of the settings that didn't change are kept.
+    pub fn save(&self) -> Option<()> {
+        let contents = match toml::to_string_pretty(self) {
+            Ok(contents) => contents,
+            Err(err) => {
+                printerror!("Could not convert project to toml. {err}");
+                return None;
+            }
+        };
+        let contents = match fs::read_to_string(&self.path).ok().and_then(|x| x.parse::<DocumentMut>().ok()) {
+            Some(mut document) => {
+                // Serialized by toml, so it always parses.
+                let new: DocumentMut = contents.parse().unwrap();
+                update_table(document.as_table_mut(), new.as_table());
+                document.to_string()
+            },
+            None => contents,
+        };
+        if let Err(err) = fs::write(&self.path, contents) {
+            printerror!("Could not write project file {:?}. {err}", self.path);
+            return None;
+        }
+        return Some(());
+    }
+
+    /// $working_dir/requirements.csv
+    pub fn spreadsheet_path(&self) -> PathBuf {
+        return self.working_dir.join("requirements.csv");
+    }
+
+    pub fn add_objective(&mut self, version: &Version, objective: Objective) -> Option<()> {
+        if self.objectives.contains_key(version) {
+            printerror!("Objective {version} already exists in project {}.", self.name);
+            return None;
+        }
+        self.objectives.insert(version.clone(), objective);
+        return Some(());
+    }
+
+    pub fn remove_objective(&mut self, version: &Version) -> Option<Objective> {
+        let objective = self.objectives.remove(version);
+        if objective.is_none() {
+            printerror!("Objective {version} does not exist in project {}.", self.name);
+        }
+        return objective;
+    }
+
+    /**
+     * Mark objectives whose requirements are all done as completed on $today, then advance the
+     * project's version (see advance_out_of_order). Completed objectives stay completed, and the
+     * version never goes backwards. Returns the newly completed objectives.
+     */
+    pub fn update_objectives(&mut self, reqs: &[Requirement], today: NaiveDate) -> Vec<Version> {
+        let mut completed: Vec<Version> = Vec::new();
+        for (version, objective) in self.objectives.iter_mut().filter(|(_, x)| x.completed.is_none()) {
+            let reqs: Vec<&Requirement> = reqs.iter().filter(|x| objective_version(x).as_ref() == Some(version)).collect();
+            if !reqs.is_empty() && reqs.iter().all(|x| x.is_complete()) {
+                objective.completed = Some(today);
+                completed.push(version.clone());
+            }
+        }
+
+        let reached = if self.advance_out_of_order {
+            self.objectives.iter().filter(|(_, x)| x.completed.is_some()).map(|(version, _)| version).last()
+        } else {
+            self.objectives.iter().take_while(|(_, x)| x.completed.is_some()).map(|(version, _)| version).last()
+        };
+        if let Some(reached) = reached {
+            if self.version.as_ref().is_none_or(|x| x < reached) {
+                self.version = Some(reached.clone());
+            }
+        }
+        return completed;
+    }
+}
+
+/// Make $table match $new, leaving the keys whose values didn't change as they were written.
+fn update_table(table: &mut Table, new: &Table) {
+    let removed: Vec<String> = table.iter().map(|(key, _)| key.to_string()).filter(|x| !new.contains_key(x)).collect();
+    for key in removed {
+        table.remove(&key);
+    }
+    for (key, item) in new.iter() {
+        match (table.get_mut(key), item) {
+            (Some(Item::Table(old)), Item::Table(new)) => update_table(old, new),
+            (Some(Item::Value(old)), Item::Value(new)) => {
+                if !same_value(old, new) {
+                    let decor = old.decor().clone();
+                    *old = new.clone();
+                    *old.decor_mut() = decor;
+                }
+            },
+            (Some(old), _) => *old = item.clone(),
+            (None, _) => {
+                table.insert(key, item.clone());
+            },
+        }
+    }
+}
+
+/// Whether $a and $b hold the same value, however they are written.
+fn same_value(a: &Value, b: &Value) -> bool {
+    return match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Float(a), Value::Float(b)) => a.value() == b.value(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Datetime(a), Value::Datetime(b)) => a.value() == b.value(),
+        (Value::Array(a), Value::Array(b)) => a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| same_value(a, b)),
+        (Value::InlineTable(a), Value::InlineTable(b)) => {
+            a.len() == b.len() && a.iter().all(|(key, a)| b.get(key).is_some_and(|b| same_value(a, b)))
+        },
+        _ => false,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(value: &str) -> Version {
+        return value.parse().unwrap();
+    }
+
+    #[test]
+    fn project_round_trip() {
+        let path = std::env::temp_dir().join(format!("odysseus-project-{}.toml", std::process::id()));
+        fs::write(&path, "working_dir = \"/tmp/ody\"\nversion = \"0.1.0\"\nunknown = 5\n\n[objectives.\"1.0.0\"]\nname = \"MVP\"\n").unwrap();
+
+        let mut project = Project::read(&path).unwrap();
+        assert_eq!(project.version, Some(version("0.1.0")));
+        assert_eq!(project.objectives[&version("1.0.0")].name, "MVP");
+        assert!(project.add_objective(&version("1.0.0"), Objective::default()).is_none());
+        project.add_objective(&version("1.1.0"), Objective {
+            name: "Next".to_string(),
+            due: NaiveDate::from_ymd_opt(2026, 12, 1),
+            ..Default::default()
+        }).unwrap();
+        assert!(project.remove_objective(&version("1.0.0")).is_some());
+        project.save().unwrap();
+
+        let project = Project::read(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(project.spreadsheet_path(), PathBuf::from("/tmp/ody/requirements.csv"));
+        assert_eq!(project.other["unknown"].as_integer(), Some(5));
+        assert_eq!(project.objectives.keys().map(|x| x.to_string()).collect::<Vec<String>>(), vec!["1.1.0"]);
+        assert_eq!(project.objectives[&version("1.1.0")].due, NaiveDate::from_ymd_opt(2026, 12, 1));
+    }
+    #[test]
+    fn save_keeps_formatting() {
+        let path = std::env::temp_dir().join(format!("odysseus-project-formatting-{}.toml", std::process::id()));
+        let contents = "# Odysseus itself.\nversion = \"0.1.0\"  # bumped by ody\nworking_dir = '/tmp/ody'\nunknown = [ 1, 2 ]\n\n# First release.\n[objectives.\"1.0.0\"]\nname = \"MVP\"\n";
+        fs::write(&path, contents).unwrap();
+
+        let mut project = Project::read(&path).unwrap();
+        project.add_objective(&version("1.1.0"), Objective { name: "Next".to_string(), ..Default::default() }).unwrap();
+        project.version = Some(version("0.2.0"));
+        project.save().unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+
+        project.remove_objective(&version("1.1.0")).unwrap();
+        project.version = Some(version("0.1.0"));
+        project.save().unwrap();
+        let restored = fs::read_to_string(&path).unwrap();
+        let _ = fs::remove_file(&path);
+
+        assert!(saved.starts_with("# Odysseus itself.\nversion = \"0.2.0\"  # bumped by ody\nworking_dir = '/tmp/ody'\nunknown = [ 1, 2 ]\n\n# First release.\n[objectives.\"1.0.0\"]\nname = \"MVP\"\n"), "{saved}");
+        assert!(saved.contains("[objectives.\"1.1.0\"]\nname = \"Next\"\n"), "{saved}");
+        assert_eq!(restored, contents);
+    }
+    #[test]
+    fn invalid_project_version() {
+        assert!(toml::from_str::<Project>("version = \"1.0\"\n").is_err());
+        assert!(toml::from_str::<Project>("[objectives.\"1.x.0\"]\nname = \"MVP\"\n").is_err());
+    }
+    #[test]
+    fn update_objectives() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 18).unwrap();
+        let mut project = Project { version: Some(version("0.9.0")), ..Default::default() };
+        for version in ["1.0.0-beta", "1.0.0", "1.1.0"] {
+            project.objectives.insert(version.parse().unwrap(), Objective::default());
+        }
+        let mut reqs = vec![Requirement::test("h1", 1).in_category("PROJ").with_objective("1.0.0-beta"), Requirement::test("h2", 0).in_category("PROJ").with_objective("1.0.0"), Requirement::test("h3", 1).in_category("PROJ").with_objective("1.1.0")];
+
+        // 1.1.0 is done before 1.0.0, so the version stops at the beta.
+        assert_eq!(project.update_objectives(&reqs, today), vec!["1.0.0-beta".parse().unwrap(), version("1.1.0")]);
+        assert_eq!(project.version, Some("1.0.0-beta".parse().unwrap()));
+        assert_eq!(project.objectives[&version("1.1.0")].completed, Some(today));
+
+        let mut skipping = project.clone();
+        skipping.advance_out_of_order = true;
+        skipping.update_objectives(&reqs, today);
+        assert_eq!(skipping.version, Some(version("1.1.0")));
+
+        reqs[1].status = 1;
+        assert_eq!(project.update_objectives(&reqs, today), vec![version("1.0.0")]);
+        assert_eq!(project.version, Some(version("1.1.0")));
+    }
+}
@@ -0,0 +1,41 @@
+use mythos_core::printerror;
+
+use crate::requirements::{find_requirements, EditableFile, Requirement};
+use super::Version;
+
+/**
+ * Set the objective of every requirement matching one of the patterns (see find_requirements),
+ * or clear it if objective is None. Returns the hashes of the changed requirements.
+ */
+pub fn assign_objective(sheet: &mut EditableFile, patterns: &[String], objective: Option<&Version>) -> Option<Vec<String>> {
+    let mut hashes: Vec<String> = Vec::new();
+    for pattern in patterns {
+        for req in find_requirements(&sheet.requirements, pattern)? {
+            if !hashes.contains(&req.hash) {
+                hashes.push(req.hash.clone());
+            }
+        }
+    }
+    let objective = objective.map(|x| x.to_string());
+    for hash in &hashes {
+        sheet.set_objective(hash, objective.as_deref())?;
+    }
+    return Some(hashes);
+}
+
+/// The requirement's objective, if it has a valid one. See check_objectives.
+pub fn objective_version(req: &Requirement) -> Option<Version> {
+    return req.objective.as_deref()?.parse().ok();
+}
+
+/// Print an error for every requirement whose objective isn't a valid version. Returns whether all are valid.
+pub fn check_objectives<'a>(reqs: impl IntoIterator<Item = &'a Requirement>) -> bool {
+    let mut is_valid = true;
+    for req in reqs {
+        if let Some(Err(err)) = req.objective.as_deref().map(|x| x.parse::<Version>()) {
+            printerror!("Invalid objective for {} {} (@{}). {err}", req.category, req.id_to_string(), req.hash);
+            is_valid = false;
+        }
+    }
+    return is_valid;
+}
@@ -0,0 +1,168 @@
+use chrono::NaiveDate;
+
+use crate::requirements::{Progress, Requirement};
+use super::{objective_version, Project, Version};
+
+/// Requirements grouped by objective, in version order.
+#[derive(Debug, Clone)]
+pub struct Roadmap {
+    pub project: String,
+    pub version: Option<Version>,
+    pub objectives: Vec<RoadmapObjective>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RoadmapObjective {
+    pub version: Version,
+    pub name: String,
+    pub due: Option<NaiveDate>,
+    /// Date recorded in the project file.
+    pub completed: Option<NaiveDate>,
+    pub progress: Progress,
+    /// Requirements still to be done, in list order.
+    pub open: Vec<Requirement>,
+}
+
+impl RoadmapObjective {
+    /// Recorded as completed, or every requirement is done.
+    pub fn is_complete(&self) -> bool {
+        return self.completed.is_some() || (self.progress.total > 0 && self.progress.done == self.progress.total);
+    }
+
+    fn title(&self) -> String {
+        if self.name.is_empty() {
+            return self.version.to_string();
+        }
+        return format!("{} - {}", self.version, self.name);
+    }
+}
+
+impl Roadmap {
+    /**
+     * Objectives come from the project file, as well as any only found in the spreadsheet.
+     * Requirements with invalid objectives are left out, see check_objectives.
+     */
+    pub fn new(project: &Project, reqs: &[Requirement]) -> Roadmap {
+        let mut versions: Vec<Version> = project.objectives.keys().cloned().collect();
+        for version in reqs.iter().filter_map(objective_version) {
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+        versions.sort();
+
+        let objectives = versions.into_iter().map(|version| {
+            let objective = project.objectives.get(&version).cloned().unwrap_or_default();
+            let reqs: Vec<&Requirement> = reqs.iter().filter(|x| objective_version(x).as_ref() == Some(&version)).collect();
+            RoadmapObjective {
+                version,
+                name: objective.name,
+                due: objective.due,
+                completed: objective.completed,
+                progress: Progress {
+                    done: reqs.iter().filter(|x| x.is_complete()).count(),
+                    total: reqs.len(),
+                },
+                open: reqs.into_iter().filter(|x| !x.is_complete()).cloned().collect(),
+            }
+        }).collect();
+
+        return Roadmap {
+            project: project.name.clone(),
+            version: project.version.clone(),
+            objectives,
+        };
+    }
+
+    pub fn to_text_format(&self) -> String {
+        let version = self.version.as_ref().map(|x| x.to_string()).unwrap_or("(no version)".to_string());
+        let mut output = format!("{} {version}\n", self.project);
+        for objective in &self.objectives {
+            output += &format!("\n[{}] {}", if objective.is_complete() { "x" } else { " " }, objective.title());
+            output += &format!(" ({}/{} done", objective.progress.done, objective.progress.total);
+            output += &match (objective.completed, objective.due) {
+                (Some(date), _) => format!(", completed {date})\n"),
+                (None, Some(date)) => format!(", due {date})\n"),
+                _ => ")\n".to_string(),
+            };
+            for req in &objective.open {
+                output += &format!("    {} {} {}\n", req.category, req.id_to_string(), req.contents);
+            }
+        }
+        return output;
+    }
+
+    pub fn to_md_format(&self) -> String {
+        let mut output = "# Roadmap\n".to_string();
+        if let Some(version) = &self.version {
+            output += &format!("Current version: **{version}**\n");
+        }
+        for objective in &self.objectives {
+            output += &format!("\n## {}\n", objective.title());
+            let mut status = match (objective.completed, objective.is_complete(), objective.due) {
+                (Some(date), _, _) => format!("Completed {date}. "),
+                (None, true, _) => "Completed. ".to_string(),
+                (None, false, Some(date)) => format!("Due {date}. "),
+                _ => String::new(),
+            };
+            status += &format!("{}/{} requirements done.\n", objective.progress.done, objective.progress.total);
+            output += &status;
+            if !objective.open.is_empty() {
+                output += "\n";
+            }
+            for req in &objective.open {
+                output += &format!("- [ ] {} ({}.{})\n", req.contents, req.category, req.id_to_string());
+            }
+        }
+        return output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::project::Objective;
+
+    fn version(value: &str) -> Version {
+        return value.parse().unwrap();
+    }
+
+    fn roadmap() -> Roadmap {
+        let mut project = Project { name: "ody".to_string(), version: Some(version("0.9.0")), ..Default::default() };
+        project.objectives.insert(version("0.9.0"), Objective {
+            name: "MVP".to_string(),
+            completed: NaiveDate::from_ymd_opt(2026, 10, 1),
+            ..Default::default()
+        });
+        project.objectives.insert(version("0.10.0"), Objective {
+            name: "Next".to_string(),
+            due: NaiveDate::from_ymd_opt(2026, 12, 1),
+            ..Default::default()
+        });
+        let reqs = vec![
+            Requirement::test("h1", 1).in_category("PROJ").with_id(&[1]).with_contents("Item h1.").with_objective("0.9.0"),
+            Requirement::test("h2", 0).in_category("PROJ").with_id(&[2]).with_contents("Item h2.").with_objective("0.10.0"),
+            Requirement::test("h3", 1).in_category("PROJ").with_id(&[2, 1]).with_contents("Item h3.").with_objective("0.10.0"),
+            Requirement::test("h4", 0).in_category("PROJ").with_id(&[3]).with_contents("Item h4.").with_objective("1.0.0"),
+            Requirement::test("h5", 0).in_category("PROJ").with_id(&[4]).with_contents("Item h5.").with_objective("1.0.0-beta"),
+            Requirement::test("h6", 0).in_category("PROJ").with_id(&[5]).with_contents("Item h6.").with_objective("1.0"),
+        ];
+        return Roadmap::new(&project, &reqs);
+    }
+
+    #[test]
+    fn roadmap_order() {
+        let roadmap = roadmap();
+        let versions: Vec<String> = roadmap.objectives.iter().map(|x| x.version.to_string()).collect();
+        assert_eq!(versions, vec!["0.9.0", "0.10.0", "1.0.0-beta", "1.0.0"]);
+        assert!(roadmap.objectives[0].is_complete());
+        assert_eq!(roadmap.objectives[1].progress, Progress { done: 1, total: 2 });
+        assert_eq!(roadmap.objectives[1].open[0].hash, "h2");
+    }
+    #[test]
+    fn roadmap_formats() {
+        let roadmap = roadmap();
+        assert_eq!(roadmap.to_text_format(), "ody 0.9.0\n\n[x] 0.9.0 - MVP (1/1 done, completed 2026-10-01)\n\n[ ] 0.10.0 - Next (1/2 done, due 2026-12-01)\n    PROJ 2 Item h2.\n\n[ ] 1.0.0-beta (0/1 done)\n    PROJ 4 Item h5.\n\n[ ] 1.0.0 (0/1 done)\n    PROJ 3 Item h4.\n");
+        assert!(roadmap.to_md_format().starts_with("# Roadmap\nCurrent version: **0.9.0**\n\n## 0.9.0 - MVP\nCompleted 2026-10-01. 1/1 requirements done.\n\n## 0.10.0 - Next\nDue 2026-12-01. 1/2 requirements done.\n\n- [ ] Item h2. (PROJ.2)\n"));
+    }
+}
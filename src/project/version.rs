@@ -0,0 +1,152 @@
+use std::{cmp::Ordering, fmt::Display, str::FromStr};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+/**
+ * Semantic version, used for a project's version and its objectives.
+ * Pre-release tags (1.0.0-beta.2) are supported and sort before the release. Build metadata is not.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+    /// Dot separated pre-release identifiers, e.g. ["beta", "2"].
+    pub pre: Vec<String>,
+}
+
+impl Version {
+    pub fn is_prerelease(&self) -> bool {
+        return !self.pre.is_empty();
+    }
+}
+
+impl FromStr for Version {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim().trim_start_matches('v');
+        if value.is_empty() {
+            return Err("Version is empty.".to_string());
+        }
+        if value.contains('+') {
+            return Err(format!("\"{value}\" has build metadata, which is not supported."));
+        }
+        let (core, pre) = match value.split_once('-') {
+            Some((core, pre)) => (core, Some(pre)),
+            None => (value, None),
+        };
+
+        let parts: Vec<&str> = core.split('.').collect();
+        if parts.len() != 3 {
+            return Err(format!("\"{value}\" should have the form major.minor.patch, e.g. 1.0.0."));
+        }
+        let mut numbers = [0; 3];
+        for (i, part) in parts.iter().enumerate() {
+            if part.len() > 1 && part.starts_with('0') {
+                return Err(format!("\"{value}\" has a leading zero in \"{part}\"."));
+            }
+            numbers[i] = match part.parse::<u64>() {
+                Ok(number) => number,
+                Err(_) => return Err(format!("\"{value}\" has a non-numeric part \"{part}\".")),
+            };
+        }
+
+        let pre: Vec<String> = match pre {
+            Some(pre) => pre.split('.').map(|x| x.to_string()).collect(),
+            None => Vec::new(),
+        };
+        for id in &pre {
+            if id.is_empty() || !id.chars().all(|x| x.is_ascii_alphanumeric() || x == '-') {
+                return Err(format!("\"{value}\" has an invalid pre-release tag \"{id}\"."));
+            }
+            if id.len() > 1 && id.starts_with('0') && id.chars().all(|x| x.is_ascii_digit()) {
+                return Err(format!("\"{value}\" has a leading zero in \"{id}\"."));
+            }
+        }
+        return Ok(Version { major: numbers[0], minor: numbers[1], patch: numbers[2], pre });
+    }
+}
+
+impl Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+        if self.is_prerelease() {
+            write!(f, "-{}", self.pre.join("."))?;
+        }
+        return Ok(());
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let core = (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch));
+        if core != Ordering::Equal {
+            return core;
+        }
+        // A release comes after all of its pre-releases.
+        return match (self.is_prerelease(), other.is_prerelease()) {
+            (false, false) => Ordering::Equal,
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (true, true) => {
+                for (a, b) in self.pre.iter().zip(&other.pre) {
+                    // Numeric identifiers are compared as numbers, and come before alphanumeric ones.
+                    let order = match (a.parse::<u64>(), b.parse::<u64>()) {
+                        (Ok(a), Ok(b)) => a.cmp(&b),
+                        (Ok(_), Err(_)) => Ordering::Less,
+                        (Err(_), Ok(_)) => Ordering::Greater,
+                        (Err(_), Err(_)) => a.cmp(b),
+                    };
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                }
+                self.pre.len().cmp(&other.pre.len())
+            }
+        };
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Serialize for Version {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        return serializer.serialize_str(&self.to_string());
+    }
+}
+
+impl<'de> Deserialize<'de> for Version {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        return String::deserialize(deserializer)?.parse().map_err(D::Error::custom);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(value: &str) -> Version {
+        return value.parse().unwrap();
+    }
+
+    #[test]
+    fn parse_versions() {
+        assert_eq!(version("1.2.3"), Version { major: 1, minor: 2, patch: 3, pre: Vec::new() });
+        assert_eq!(version("v1.0.0-beta.2").pre, vec!["beta", "2"]);
+        assert_eq!(version("1.0.0-rc.1").to_string(), "1.0.0-rc.1");
+        for invalid in ["", "1", "1.0", "1.0.0.0", "1.x.0", "01.0.0", "1.0.0-", "1.0.0-beta..1", "1.0.0-beta_1", "1.0.0+build"] {
+            assert!(invalid.parse::<Version>().is_err(), "{invalid} should be invalid");
+        }
+    }
+    #[test]
+    fn order_versions() {
+        let ordered = ["0.9.0", "0.10.0", "1.0.0-alpha", "1.0.0-alpha.1", "1.0.0-alpha.beta", "1.0.0-beta", "1.0.0-beta.2", "1.0.0-beta.11", "1.0.0-rc.1", "1.0.0", "1.1.0"];
+        for pair in ordered.windows(2) {
+            assert!(version(pair[0]) < version(pair[1]), "{} < {}", pair[0], pair[1]);
+        }
+    }
+}
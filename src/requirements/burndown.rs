@@ -0,0 +1,227 @@
+use std::collections::HashMap;
+use chrono::{Days, NaiveDate};
+use mythos_core::printerror;
+use serde::Serialize;
+
+use super::{Requirement, StatusChange};
+
+const BURNDOWN_HEADER: &str = "Date,Group,Open,Done,Completed\n";
+const CHART_WIDTH: usize = 40;
+/// Days of completions the velocity in the chart is averaged over.
+const VELOCITY_WINDOW: usize = 14;
+
+/// What the requirements are counted by. Project counts all of them as one group.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BurndownGroup { Project, Category, Objective }
+
+/// Requirement counts of a group at the end of a day. Completed is how many were marked done that day.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BurndownDay {
+    pub date: NaiveDate,
+    pub group: String,
+    pub open: usize,
+    pub done: usize,
+    pub completed: usize,
+}
+
+/// Per day counts for every group, ordered by group (in the order they first appear) and then by date.
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
+pub struct Burndown {
+    pub days: Vec<BurndownDay>,
+}
+
+impl Burndown {
+    /**
+     * Replay a spreadsheet's history, from the first recorded change up to $today, for the requirements in
+     * $reqs. Requirements with no history are counted with their current status from the first day, and
+     * requirements first recorded as created only count from the day they were created.
+     * Changes to requirements that are no longer in $reqs are ignored.
+     */
+    pub fn new(reqs: &[Requirement], history: &[StatusChange], group_by: BurndownGroup, today: NaiveDate) -> Burndown {
+        let mut groups: Vec<String> = Vec::new();
+        let mut group_of: HashMap<&str, usize> = HashMap::new();
+        for req in reqs {
+            let key = match group_by {
+                BurndownGroup::Project => "total".to_string(),
+                BurndownGroup::Category => req.category.to_string(),
+                BurndownGroup::Objective => match &req.objective {
+                    Some(objective) => objective.clone(),
+                    None => continue,
+                },
+            };
+            let i = match groups.iter().position(|x| *x == key) {
+                Some(i) => i,
+                None => {
+                    groups.push(key);
+                    groups.len() - 1
+                }
+            };
+            group_of.insert(&req.hash, i);
+        }
+
+        let history: Vec<&StatusChange> = history.iter().filter(|x| group_of.contains_key(x.hash.as_str())).collect();
+        let Some(start) = history.iter().map(|x| x.timestamp.date_naive()).min() else {
+            return Burndown { days: Vec::new() };
+        };
+        let end = history.iter().map(|x| x.timestamp.date_naive()).max().unwrap_or(start).max(today);
+
+        // Status before the first recorded change. None until the requirement is created.
+        let mut status: HashMap<&str, Option<u8>> = reqs.iter().map(|x| (x.hash.as_str(), Some(x.status))).collect();
+        for change in history.iter().rev() {
+            status.insert(&change.hash, change.old);
+        }
+
+        let mut rows: Vec<Vec<BurndownDay>> = vec![Vec::new(); groups.len()];
+        let mut changes = history.iter().peekable();
+        let mut date = start;
+        while date <= end {
+            let mut completed = vec![0; groups.len()];
+            while let Some(change) = changes.next_if(|x| x.timestamp.date_naive() <= date) {
+                if change.new == 1 && change.old.is_some_and(|x| x != 1) {
+                    completed[group_of[change.hash.as_str()]] += 1;
+                }
+                status.insert(&change.hash, Some(change.new));
+            }
+
+            let mut counts = vec![(0, 0); groups.len()];
+            for (hash, status) in &status {
+                let (Some(status), Some(i)) = (status, group_of.get(hash)) else { continue };
+                match status {
+                    1 => counts[*i].1 += 1,
+                    _ => counts[*i].0 += 1,
+                }
+            }
+            for (i, (open, done)) in counts.into_iter().enumerate() {
+                rows[i].push(BurndownDay { date, group: groups[i].clone(), open, done, completed: completed[i] });
+            }
+            date = date + Days::new(1);
+        }
+        return Burndown { days: rows.into_iter().flatten().collect() };
+    }
+
+    pub fn to_csv_format(&self) -> String {
+        let mut output = BURNDOWN_HEADER.to_string();
+        for day in &self.days {
+            output += &format!("{},{},{},{},{}\n", day.date, day.group, day.open, day.done, day.completed);
+        }
+        return output;
+    }
+
+    pub fn to_json_format(&self) -> Option<String> {
+        return match serde_json::to_string_pretty(self) {
+            Ok(output) => Some(output + "\n"),
+            Err(err) => {
+                printerror!("Could not convert burndown to json. {err}");
+                None
+            }
+        };
+    }
+
+    /**
+     * A bar per day for each group, done requirements as # and open ones as -, followed by the average
+     * velocity of the last two weeks and when the open requirements would be done at that rate.
+     */
+    pub fn to_chart_format(&self) -> String {
+        let mut output = String::new();
+        let mut groups: Vec<&str> = Vec::new();
+        for day in &self.days {
+            if !groups.contains(&day.group.as_str()) {
+                groups.push(&day.group);
+            }
+        }
+        for group in groups {
+            let days: Vec<&BurndownDay> = self.days.iter().filter(|x| x.group == group).collect();
+            let max = days.iter().map(|x| x.open + x.done).max().unwrap_or(0).max(1);
+            if !output.is_empty() {
+                output += "\n";
+            }
+            output += &format!("{group}\n");
+            for day in &days {
+                // Round up, so that a single requirement is still visible.
+                let done = (day.done * CHART_WIDTH).div_ceil(max);
+                let open = ((day.open + day.done) * CHART_WIDTH).div_ceil(max) - done;
+                let completed = if day.completed > 0 { format!(" (+{})", day.completed) } else { String::new() };
+                output += &format!("{} |{}{}{}| {} open, {} done{completed}\n", day.date, "#".repeat(done), "-".repeat(open), " ".repeat(CHART_WIDTH - done - open), day.open, day.done);
+            }
+
+            let Some(last) = days.last() else { continue };
+            let window = &days[days.len().saturating_sub(VELOCITY_WINDOW)..];
+            let velocity = window.iter().map(|x| x.completed).sum::<usize>() as f64 / window.len() as f64;
+            output += &format!("Velocity: {velocity:.2} per day over the last {} day(s). ", window.len());
+            if last.open == 0 {
+                output += "Everything is done.\n";
+            } else if velocity == 0.0 {
+                output += &format!("{} open, not done at this rate.\n", last.open);
+            } else {
+                let remaining = (last.open as f64 / velocity).ceil() as u64;
+                output += &format!("{} open, done around {} at this rate.\n", last.open, last.date + Days::new(remaining));
+            }
+        }
+        return output;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, FixedOffset};
+
+    use super::*;
+    use crate::requirements::StatusSource;
+
+    fn change(hash: &str, old: Option<u8>, new: u8, timestamp: &str) -> StatusChange {
+        let timestamp: DateTime<FixedOffset> = DateTime::parse_from_rfc3339(timestamp).unwrap();
+        return StatusChange::new(hash, old, new, timestamp, StatusSource::List);
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(2026, 10, day).unwrap();
+    }
+
+    fn history() -> (Vec<Requirement>, Vec<StatusChange>) {
+        let reqs = vec![Requirement::test("h1", 1).in_category("CAT").with_objective("1.0.0"), Requirement::test("h2", 0).in_category("CAT").with_objective("1.0.0"), Requirement::test("h3", 1).in_category("OTHR"), Requirement::test("h4", 1).in_category("OTHR").with_objective("1.0.0")];
+        let history = vec![
+            change("h1", None, 0, "2026-10-01T09:00:00+02:00"),
+            change("h2", None, 0, "2026-10-01T09:00:00+02:00"),
+            change("h4", Some(0), 1, "2026-10-02T09:00:00+02:00"),
+            change("h1", Some(0), 1, "2026-10-03T09:00:00+02:00"),
+            change("h5", Some(0), 1, "2026-10-03T09:00:00+02:00"),
+            change("h2", None, 0, "2026-10-03T10:00:00+02:00"),
+        ];
+        return (reqs, history);
+    }
+
+    #[test]
+    fn count_per_day() {
+        let (reqs, history) = history();
+        let counts = |group_by| Burndown::new(&reqs, &history, group_by, date(4)).days.iter()
+            .map(|x| (x.date, x.group.clone(), x.open, x.done, x.completed))
+            .collect::<Vec<_>>();
+        assert_eq!(counts(BurndownGroup::Project), vec![
+            (date(1), "total".to_string(), 3, 1, 0),
+            (date(2), "total".to_string(), 2, 2, 1),
+            (date(3), "total".to_string(), 1, 3, 1),
+            (date(4), "total".to_string(), 1, 3, 0),
+        ]);
+        let by_category = counts(BurndownGroup::Category);
+        assert_eq!(by_category[3], (date(4), "CAT".to_string(), 1, 1, 0));
+        assert_eq!(by_category[4], (date(1), "OTHR".to_string(), 1, 1, 0));
+        let by_objective = counts(BurndownGroup::Objective);
+        assert_eq!(by_objective.len(), 4);
+        assert_eq!(by_objective[0], (date(1), "1.0.0".to_string(), 3, 0, 0));
+
+        let burndown = Burndown::new(&reqs, &history, BurndownGroup::Objective, date(2));
+        assert_eq!(burndown.to_csv_format().lines().nth(1), Some("2026-10-01,1.0.0,3,0,0"));
+        assert!(Burndown::new(&reqs, &[], BurndownGroup::Project, date(4)).days.is_empty());
+    }
+    #[test]
+    fn burndown_chart() {
+        let (reqs, history) = history();
+        let chart = Burndown::new(&reqs, &history, BurndownGroup::Objective, date(3)).to_chart_format();
+        let lines: Vec<&str> = chart.lines().collect();
+        assert_eq!(lines[0], "1.0.0");
+        assert_eq!(lines[1], format!("2026-10-01 |{}| 3 open, 0 done", "-".repeat(40)));
+        assert_eq!(lines[3], format!("2026-10-03 |{}{}| 1 open, 2 done (+1)", "#".repeat(27), "-".repeat(13)));
+        assert_eq!(lines[4], "Velocity: 0.67 per day over the last 3 day(s). 1 open, done around 2026-10-05 at this rate.");
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use mythos_core::printerror;
+
+use super::{Change, Requirement, RequirementDiff};
+
+const GREEN: &str = "\x1b[32m";
+const RED: &str = "\x1b[31m";
+const YELLOW: &str = "\x1b[33m";
+const RESET: &str = "\x1b[0m";
+
+/// Compare the current spreadsheet with what it would become.
+/// Changed and added items are in the order of the new version, followed by removed items.
+pub fn diff_requirements(before: &HashMap<String, Requirement>, after: &[Requirement]) -> Vec<RequirementDiff> {
+    let mut output: Vec<RequirementDiff> = Vec::new();
+
+    for req in after {
+        let prev = match before.get(&req.hash) {
+            Some(prev) => prev,
+            None => {
+                output.push(RequirementDiff::new(req, vec![Change::Added]));
+                continue;
+            }
+        };
+        let mut changes: Vec<Change> = Vec::new();
+        if prev.id != req.id {
+            changes.push(Change::Moved { from: prev.id_to_string(), to: req.id_to_string() });
+        }
+        if prev.contents != req.contents {
+            changes.push(Change::Renamed { from: prev.contents.clone(), to: req.contents.clone() });
+        }
+        if prev.status != req.status {
+            changes.push(Change::StatusChanged { from: prev.status, to: req.status });
+        }
+        if prev.category != req.category {
+            changes.push(Change::Recategorised { from: prev.category.to_string(), to: req.category.to_string() });
+        }
+        if !changes.is_empty() {
+            output.push(RequirementDiff::new(req, changes));
+        }
+    }
+
+    let mut removed: Vec<&Requirement> = before.values()
+        .filter(|x| !after.iter().any(|y| y.hash == x.hash))
+        .collect();
+    removed.sort_by(|a, b| (&a.category, &a.id).cmp(&(&b.category, &b.id)));
+    for req in removed {
+        output.push(RequirementDiff::new(req, vec![Change::Removed]));
+    }
+
+    return output;
+}
+
+impl RequirementDiff {
+    fn new(req: &Requirement, changes: Vec<Change>) -> RequirementDiff {
+        return RequirementDiff {
+            hash: req.hash.clone(),
+            category: req.category.to_string(),
+            id: req.id_to_string(),
+            contents: req.contents.clone(),
+            changes,
+        };
+    }
+
+    /// Format a list of diffs for the terminal, ending with a summary line.
+    pub fn to_report(diffs: &[RequirementDiff], use_color: bool) -> String {
+        let color = |code: &'static str| if use_color { code } else { "" };
+        let (mut added, mut removed, mut changed) = (0, 0, 0);
+        let mut output = String::new();
+
+        for diff in diffs {
+            let (mark, code) = match diff.changes.first() {
+                Some(Change::Added) => { added += 1; ('+', GREEN) },
+                Some(Change::Removed) => { removed += 1; ('-', RED) },
+                _ => { changed += 1; ('~', YELLOW) },
+            };
+            output += &format!("{code}{mark} {cat} {id} {contents}(@{hash}){reset}\n",
+                code=color(code),
+                cat=diff.category,
+                id=diff.id,
+                contents=diff.contents,
+                hash=diff.hash,
+                reset=color(RESET));
+
+            for change in &diff.changes {
+                let line = match change {
+                    Change::Added | Change::Removed => continue,
+                    Change::Moved { from, to } => format!("moved: {from} -> {to}"),
+                    Change::Renamed { from, to } => format!("renamed: \"{from}\" -> \"{to}\""),
+                    Change::StatusChanged { from, to } => format!("status: {from} -> {to}"),
+                    Change::Recategorised { from, to } => format!("recategorised: {from} -> {to}"),
+                };
+                output += &format!("    {line}\n");
+            }
+        }
+        output += &format!("{added} added, {removed} removed, {changed} changed.\n");
+        return output;
+    }
+
+    pub fn to_json_format(diffs: &[RequirementDiff]) -> Option<String> {
+        return match serde_json::to_string(diffs) {
+            Ok(output) => Some(output + "\n"),
+            Err(err) => {
+                printerror!("Could not convert diff to json. {err}");
+                None
+            }
+        };
+    }
+
+    pub fn to_yaml_format(diffs: &[RequirementDiff]) -> Option<String> {
+        return match serde_yaml::to_string(diffs) {
+            Ok(output) => Some(output),
+            Err(err) => {
+                printerror!("Could not convert diff to yaml. {err}");
+                None
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diff_spreadsheets() {
+        let before: HashMap<String, Requirement> = vec![
+            Requirement::test("h1", 0).in_category("CAT").with_id(&[1]).with_contents("Same."),
+            Requirement::test("h2", 0).in_category("CAT").with_id(&[2]).with_contents("Old."),
+            Requirement::test("h3", 0).in_category("CAT").with_id(&[3]).with_contents("Removed."),
+        ].into_iter().map(|x| (x.hash.clone(), x)).collect();
+        let after = vec![
+            Requirement::test("h1", 0).in_category("CAT").with_id(&[1]).with_contents("Same."),
+            Requirement::test("h4", 0).in_category("CAT").with_id(&[2]).with_contents("Added."),
+            Requirement::test("h2", 1).in_category("DOG").with_id(&[1, 1]).with_contents("New."),
+        ];
+        let diffs = diff_requirements(&before, &after);
+
+        assert_eq!(diffs.len(), 3);
+        assert_eq!(diffs[0].changes, vec![Change::Added]);
+        assert_eq!(diffs[1].changes, vec![
+            Change::Moved { from: "2".to_string(), to: "1.1".to_string() },
+            Change::Renamed { from: "Old.".to_string(), to: "New.".to_string() },
+            Change::StatusChanged { from: 0, to: 1 },
+            Change::Recategorised { from: "CAT".to_string(), to: "DOG".to_string() },
+        ]);
+        assert_eq!(diffs[2].hash, "h3");
+        assert_eq!(diffs[2].changes, vec![Change::Removed]);
+
+        let report = RequirementDiff::to_report(&diffs, false);
+        assert!(report.starts_with("+ CAT 2 Added.(@h4)\n~ DOG 1.1 New.(@h2)\n    moved: 2 -> 1.1\n"));
+        assert!(report.ends_with("1 added, 1 removed, 1 changed.\n"));
+    }
+    #[test]
+    fn diff_to_json() {
+        let diffs = vec![RequirementDiff {
+            hash: "h1".to_string(),
+            category: "CAT".to_string(),
+            id: "1".to_string(),
+            contents: "Say \"hi\".".to_string(),
+            changes: vec![Change::Added, Change::StatusChanged { from: 0, to: 1 }],
+        }];
+        assert_eq!(RequirementDiff::to_json_format(&diffs).unwrap(),
+            "[{\"hash\":\"h1\",\"category\":\"CAT\",\"id\":\"1\",\"contents\":\"Say \\\"hi\\\".\",\"changes\":[{\"kind\":\"added\"},{\"kind\":\"status\",\"from\":0,\"to\":1}]}]\n");
+    }
+}
@@ -0,0 +1,169 @@
+use std::{collections::HashMap, fs::read_to_string, path::PathBuf};
+use mythos_core::{printerror, printinfo};
+use serde::{Deserialize, Deserializer, Serializer};
+
+use super::{ListItem, Requirement, RequirementSet};
+
+/// Whether path should be read/written as a json/yaml requirement set.
+pub fn is_document(path: &PathBuf) -> bool {
+    return matches!(path.extension().and_then(|x| x.to_str()), Some("json") | Some("yaml") | Some("yml"));
+}
+
+fn is_json(path: &PathBuf) -> bool {
+    return path.extension().is_some_and(|x| x == "json");
+}
+
+/// Read a json/yaml requirement set, so it can be used in place of a spreadsheet.
+pub fn parse_document(path: &PathBuf, be_verbose: bool) -> Option<HashMap<String, Requirement>> {
+    let set = RequirementSet::read(path, be_verbose)?;
+    let mut output: HashMap<String, Requirement> = HashMap::new();
+
+    for req in set.requirements {
+        if let Some(collision) = output.insert(req.hash.clone(), req.clone()) {
+            printerror!("There was a hash collision while reading the requirements file.");
+            printerror!("Original value: {collision:?}");
+            printerror!("Colliding hash: {req:?}");
+        }
+    }
+    return Some(output);
+}
+
+impl RequirementSet {
+    pub fn new(reqs: &[Requirement], categories: &HashMap<String, String>) -> RequirementSet {
+        return RequirementSet {
+            categories: categories.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+            requirements: reqs.to_vec(),
+        };
+    }
+
+    /// Spreadsheets do not keep track of order or category headers, so requirements are sorted
+    /// by category and id, and each category is its own header.
+    pub fn from_spreadsheet(sheet: HashMap<String, Requirement>) -> RequirementSet {
+        let mut requirements: Vec<Requirement> = sheet.into_values().collect();
+        requirements.sort_by(|a, b| (&a.category, &a.id).cmp(&(&b.category, &b.id)));
+        return RequirementSet {
+            categories: requirements.iter().map(|x| (x.category.to_string(), x.category.to_string())).collect(),
+            requirements,
+        };
+    }
+
+    pub fn read(path: &PathBuf, be_verbose: bool) -> Option<RequirementSet> {
+        let contents = match read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                printerror!("Could not open requirements file. {err}");
+                return None;
+            }
+        };
+        printinfo!(be_verbose, "\nReading {path:?}");
+        return if is_json(path) {
+            RequirementSet::from_json(&contents)
+        } else {
+            RequirementSet::from_yaml(&contents)
+        };
+    }
+
+    pub fn from_json(contents: &str) -> Option<RequirementSet> {
+        return match serde_json::from_str(contents) {
+            Ok(set) => Some(set),
+            Err(err) => {
+                printerror!("Could not parse json requirements. {err}");
+                None
+            }
+        };
+    }
+
+    pub fn from_yaml(contents: &str) -> Option<RequirementSet> {
+        return match serde_yaml::from_str(contents) {
+            Ok(set) => Some(set),
+            Err(err) => {
+                printerror!("Could not parse yaml requirements. {err}");
+                None
+            }
+        };
+    }
+
+    pub fn to_json(&self) -> Option<String> {
+        return match serde_json::to_string_pretty(self) {
+            Ok(output) => Some(output + "\n"),
+            Err(err) => {
+                printerror!("Could not convert requirements to json. {err}");
+                None
+            }
+        };
+    }
+
+    pub fn to_yaml(&self) -> Option<String> {
+        return match serde_yaml::to_string(self) {
+            Ok(output) => Some(output),
+            Err(err) => {
+                printerror!("Could not convert requirements to yaml. {err}");
+                None
+            }
+        };
+    }
+}
+
+pub fn serialize_id<S: Serializer>(id: &[usize], serializer: S) -> Result<S::Ok, S::Error> {
+    let id = id.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(".");
+    return serializer.serialize_str(&id);
+}
+
+pub fn deserialize_id<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<usize>, D::Error> {
+    let id = String::deserialize(deserializer)?;
+    return id.split(".")
+        .map(|x| x.trim().parse::<usize>().map_err(serde::de::Error::custom))
+        .collect();
+}
+
+/// Documents written by other tools may leave out the list item.
+pub fn default_list_item() -> ListItem {
+    return ListItem::Unordered;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    fn set() -> RequirementSet {
+        let reqs = vec![Requirement {
+            category: Rc::new("CAT".to_string()),
+            hash: "h1".to_string(),
+            id: vec![1, 2],
+            contents: "Contents.".to_string(),
+            list_item: ListItem::Hybrid(2, 'x'),
+            status: 1,
+            objective: Some("0.1.0".to_string()),
+            created_at: None,
+            completed_at: None,
+            trace: None,
+        }];
+        let categories = HashMap::from([("CAT".to_string(), "Category (CAT)".to_string())]);
+        return RequirementSet::new(&reqs, &categories);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = set().to_json().unwrap();
+        assert!(json.contains("\"id\": \"1.2\""));
+        assert!(json.contains("\"hybrid\": [\n"));
+
+        let output = RequirementSet::from_json(&json).unwrap();
+        assert_eq!(output.categories["CAT"], "Category (CAT)".to_string());
+        assert_eq!(output.requirements[0].to_text_format(), "\t2. [x] Contents.(@h1)");
+    }
+    #[test]
+    fn yaml_round_trip() {
+        let yaml = set().to_yaml().unwrap();
+        let output = RequirementSet::from_yaml(&yaml).unwrap();
+        assert_eq!(output.requirements[0].to_csv_format(), "h1,CAT,1.2,Contents.,1,0.1.0,,,\n");
+    }
+    #[test]
+    fn parse_minimal_document() {
+        let output = RequirementSet::from_json(r#"{"requirements": [{"category": "CAT", "hash": "h1", "id": "3", "contents": "A.", "status": 0}]}"#).unwrap();
+        assert!(matches!(output.requirements[0].list_item, ListItem::Unordered));
+        assert_eq!(output.requirements[0].id, vec![3]);
+    }
+}
@@ -0,0 +1,786 @@
+use std::{collections::HashMap, fs, path::PathBuf, rc::Rc};
+use chrono::{DateTime, FixedOffset};
+use mythos_core::{printerror, printinfo};
+
+use super::{is_document, is_toml, is_workbook, parse_requirements_str, parse_spreadsheet_str, parse_toml_requirements, parse_workbook, to_toml_format, ListItem, ListParser, Requirement, RequirementBuilder, RequirementSet};
+use super::history::format_timestamp;
+use super::workbook::{has_sheet_per_category, write_workbook, HEADER};
+
+/// How many hashes add generates for a new requirement before giving up on finding a unique one.
+const MAX_HASH_ATTEMPTS: usize = 100;
+
+/// How a requirements file is stored, which decides how it can be edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileKind { List, Toml, Csv, Markdown, Document, Workbook }
+
+/**
+ * A list file or spreadsheet loaded for editing. List files and csv/md spreadsheets keep their
+ * original text, so that a status change only touches the line of the changed requirement.
+ * Other formats are rewritten from scratch when saved.
+ */
+pub struct EditableFile {
+    pub path: PathBuf,
+    pub kind: FileKind,
+    pub requirements: Vec<Requirement>,
+    pub categories: HashMap<String, String>,
+    contents: String,
+    /// Set once requirements were added, moved or deleted, and the whole file must be rewritten.
+    is_restructured: bool,
+    use_sheet_per_category: bool,
+}
+
+impl EditableFile {
+    pub fn open(path: &PathBuf, be_verbose: bool) -> Option<EditableFile> {
+        let mut file = EditableFile {
+            path: path.clone(),
+            kind: FileKind::List,
+            requirements: Vec::new(),
+            categories: HashMap::new(),
+            contents: String::new(),
+            is_restructured: false,
+            use_sheet_per_category: false,
+        };
+        if is_toml(path) {
+            file.kind = FileKind::Toml;
+            (file.requirements, file.categories) = parse_toml_requirements(path, be_verbose)?;
+            return Some(file);
+        }
+        if is_document(path) || is_workbook(path) {
+            let set = if is_document(path) {
+                file.kind = FileKind::Document;
+                RequirementSet::read(path, be_verbose)?
+            } else {
+                file.kind = FileKind::Workbook;
+                file.use_sheet_per_category = has_sheet_per_category(path);
+                RequirementSet::from_spreadsheet(parse_workbook(path, be_verbose)?)
+            };
+            file.requirements = set.requirements;
+            file.categories = set.categories.into_iter().collect();
+            return Some(file);
+        }
+
+        file.contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                printerror!("Could not open requirements file {path:?}. {err}");
+                return None;
+            }
+        };
+        printinfo!(be_verbose, "Reading {path:?}");
+        let header = file.contents.lines().next().unwrap_or("");
+        if header.trim().starts_with("Hash,") || Requirement::check_md_header(header) {
+            file.kind = if header.trim().starts_with("Hash,") { FileKind::Csv } else { FileKind::Markdown };
+            let set = RequirementSet::from_spreadsheet(parse_spreadsheet_str(&file.contents, be_verbose)?);
+            file.requirements = set.requirements;
+            file.categories = set.categories.into_iter().collect();
+        } else {
+            (file.requirements, file.categories) = parse_requirements_str(&file.contents, be_verbose)?;
+        }
+        return Some(file);
+    }
+
+    pub fn is_spreadsheet(&self) -> bool {
+        return matches!(self.kind, FileKind::Csv | FileKind::Markdown | FileKind::Workbook);
+    }
+
+    pub fn get(&self, hash: &str) -> Option<&Requirement> {
+        return self.requirements.iter().find(|x| x.hash == hash);
+    }
+
+    /// Change the status of a requirement. Returns None if the hash is not in this file.
+    pub fn set_status(&mut self, hash: &str, status: u8) -> Option<()> {
+        let index = self.requirements.iter().position(|x| x.hash == hash)?;
+        self.requirements[index].set_status(status);
+
+        return match self.kind {
+            FileKind::List => {
+                let parser = ListParser::new();
+                let line_index = self.line_index(hash)?;
+                let mark = match self.requirements[index].list_item {
+                    ListItem::Todo(mark) | ListItem::Hybrid(_, mark) => mark,
+                    // Items without a mark stay as they are while open.
+                    _ => return Some(()),
+                };
+                // Only the mark is written, so the item's own header, e.g. "b." or "*", and its spacing are kept.
+                self.edit_line(line_index, |line| {
+                    let start = line.len() - line.trim_start().len();
+                    let captures = parser.0.captures(&line[start..])?;
+                    return Some(match captures.name("todo_mark").or(captures.name("htodo_mark")) {
+                        Some(old) => format!("{}{mark}{}", &line[..start + old.start()], &line[start + old.end()..]),
+                        None => {
+                            let end = start + captures.get(0)?.end();
+                            format!("{} [{mark}]{}", &line[..end], &line[end..])
+                        },
+                    });
+                })
+            },
+            FileKind::Csv | FileKind::Markdown => self.edit_row(hash, 4, &status.to_string()),
+            _ => Some(()),
+        };
+    }
+
+    /// Assign a requirement to an objective, or unassign it. Returns None if the hash is not in this file.
+    pub fn set_objective(&mut self, hash: &str, objective: Option<&str>) -> Option<()> {
+        let req = self.requirements.iter_mut().find(|x| x.hash == hash)?;
+        req.objective = objective.map(|x| x.to_string());
+
+        return match self.kind {
+            FileKind::Csv | FileKind::Markdown => self.edit_row(hash, 5, objective.unwrap_or("")),
+            _ => Some(()),
+        };
+    }
+
+    /// Set when a requirement was completed, or clear it. Returns None if the hash is not in this file.
+    pub fn set_completed_at(&mut self, hash: &str, completed_at: Option<DateTime<FixedOffset>>) -> Option<()> {
+        let req = self.requirements.iter_mut().find(|x| x.hash == hash)?;
+        req.completed_at = completed_at;
+
+        return match self.kind {
+            FileKind::Csv | FileKind::Markdown => self.edit_row(hash, 7, &format_timestamp(completed_at.as_ref())),
+            _ => Some(()),
+        };
+    }
+
+    /**
+     * Index of the line a requirement is on, for list files and csv/md spreadsheets. None for other
+     * formats, or if the hash is not in this file.
+     */
+    pub fn line_index(&self, hash: &str) -> Option<usize> {
+        let index = self.requirements.iter().position(|x| x.hash == hash)?;
+        return match self.kind {
+            FileKind::List => {
+                let parser = ListParser::new();
+                // Same lines parse_requirements_str treats as list items.
+                self.contents.split('\n')
+                    .enumerate()
+                    .filter(|(_, line)| !line.is_empty() && parser.parse(line.trim()).is_some())
+                    .nth(index)
+                    .map(|(i, _)| i)
+            },
+            FileKind::Csv | FileKind::Markdown => {
+                let separator = if self.kind == FileKind::Csv { ',' } else { '|' };
+                self.contents.split('\n')
+                    .position(|line| line.trim().trim_start_matches('|').split(separator).next().is_some_and(|x| x.trim() == hash))
+            },
+            _ => None,
+        };
+    }
+
+    /// Set where a requirement is referenced in source code, or clear it. Returns None if the hash is not in this file.
+    pub fn set_trace(&mut self, hash: &str, trace: Option<&str>) -> Option<()> {
+        let req = self.requirements.iter_mut().find(|x| x.hash == hash)?;
+        req.trace = trace.map(|x| x.to_string());
+
+        return match self.kind {
+            FileKind::Csv | FileKind::Markdown => self.edit_row(hash, 8, trace.unwrap_or("")),
+            _ => Some(()),
+        };
+    }
+
+    fn edit_line(&mut self, line_index: usize, edit: impl FnOnce(&str) -> Option<String>) -> Option<()> {
+        if self.is_restructured {
+            return Some(());
+        }
+        let mut lines: Vec<String> = self.contents.split('\n').map(|x| x.to_string()).collect();
+        lines[line_index] = edit(&lines[line_index])?;
+        self.contents = lines.join("\n");
+        return Some(());
+    }
+
+    /// Change one value in a csv/md row. Rows and header missing the column are extended to fit it.
+    fn edit_row(&mut self, hash: &str, column: usize, value: &str) -> Option<()> {
+        let (separator, offset) = if self.kind == FileKind::Csv { (',', 0) } else { ('|', 1) };
+        let header_rows = if self.kind == FileKind::Csv { 1 } else { 2 };
+        let row_index = self.line_index(hash)?;
+
+        let set_value = |line: &str, column: usize, value: &str| -> Option<String> {
+            let mut values: Vec<String> = line.trim_end().split(separator).map(|x| x.to_string()).collect();
+            // Md rows end with an empty value after the last '|'.
+            let end = if offset == 1 { values.pop() } else { None };
+            values.resize(usize::max(values.len(), column + offset + 1), String::new());
+            values[column + offset] = value.to_string();
+            values.extend(end);
+            Some(values.join(&separator.to_string()) + &line[line.trim_end().len()..])
+        };
+        for i in 0..header_rows {
+            // Only extend the header, existing titles are left alone.
+            let line = self.contents.split('\n').nth(i)?;
+            let len = line.trim().trim_matches('|').split(separator).count();
+            for title_column in len..=column {
+                let title = if i == 0 { HEADER[title_column] } else { "---" };
+                self.edit_line(i, |line| set_value(line, title_column, title))?;
+            }
+        }
+        return self.edit_line(row_index, |line| set_value(line, column, value));
+    }
+
+    /**
+     * Add a requirement as the last child of parent (or at the top level of the category, if
+     * parent is empty). Its list item style is copied from its new siblings. Returns the new hash.
+     */
+    pub fn add(&mut self, category: &str, parent: &[usize], contents: &str) -> Option<String> {
+        return self.add_with(&mut RequirementBuilder::new(), category, parent, contents);
+    }
+
+    fn add_with(&mut self, builder: &mut RequirementBuilder, category: &str, parent: &[usize], contents: &str) -> Option<String> {
+        // The builder would take the annotation as the hash, and keep returning it.
+        if builder.has_hash(contents) {
+            printerror!("Contents can't end in a (@hash). ody add generates the hash of new requirements.");
+            return None;
+        }
+        let index = self.insertion_index(category, parent)?;
+        // Regenerate the hash until it is unique.
+        let mut attempts = 0;
+        let mut req = loop {
+            let req = builder.build(contents.to_string(), parent.to_vec(), Rc::new(category.to_string()), ListItem::Ordered(0));
+            if self.get(&req.hash).is_none() {
+                break req;
+            }
+            attempts += 1;
+            if attempts == MAX_HASH_ATTEMPTS {
+                printerror!("Could not generate a unique hash for \"{contents}\". {} is already in {:?}.", req.hash, self.path);
+                return None;
+            }
+        };
+        req.id.push(0);
+        req.list_item = self.requirements.iter()
+            .find(|x| *x.category == category && x.id.len() == req.id.len() && x.id.starts_with(parent))
+            .map_or(ListItem::Ordered(0), |x| match &x.list_item {
+                ListItem::Todo(_) => ListItem::Todo(' '),
+                ListItem::Hybrid(num, _) => ListItem::Hybrid(*num, ' '),
+                item => item.clone(),
+            });
+        req.status = 0;
+
+        let hash = req.hash.clone();
+        self.categories.entry(category.to_string()).or_insert(category.to_string());
+        self.requirements.insert(index, req);
+        self.renumber();
+        return Some(hash);
+    }
+
+    /// Delete a requirement and all of its children. Returns the deleted hashes.
+    pub fn delete(&mut self, hash: &str) -> Option<Vec<String>> {
+        let (start, end) = self.subtree(hash)?;
+        let removed = self.requirements.drain(start..end).map(|x| x.hash).collect();
+        self.renumber();
+        return Some(removed);
+    }
+
+    /// Move a requirement and all of its children, to become the last child of parent in category.
+    pub fn move_to(&mut self, hash: &str, category: &str, parent: &[usize]) -> Option<()> {
+        let (start, end) = self.subtree(hash)?;
+        let depth = self.requirements[start].id.len();
+        if *self.requirements[start].category == category && parent.starts_with(&self.requirements[start].id) {
+            printerror!("Cannot move a requirement under itself.");
+            return None;
+        }
+        let subtree: Vec<Requirement> = self.requirements.drain(start..end).collect();
+        let index = match self.insertion_index(category, parent) {
+            Some(index) => index,
+            None => {
+                // Put everything back where it was.
+                self.requirements.splice(start..start, subtree);
+                return None;
+            }
+        };
+
+        let category = Rc::new(category.to_string());
+        let moved: Vec<Requirement> = subtree.into_iter().map(|mut req| {
+            // Keep the shape of the subtree; ids are fixed by renumber.
+            let mut id = parent.to_vec();
+            id.extend(&req.id[depth - 1..]);
+            req.id = id;
+            req.category = category.clone();
+            req
+        }).collect();
+        self.categories.entry(category.to_string()).or_insert(category.to_string());
+        self.requirements.splice(index..index, moved);
+        self.renumber();
+        return Some(());
+    }
+
+    /**
+     * Apply the structure of the list to this spreadsheet: deleted rows are dropped, added rows
+     * are inserted, and ids/categories are copied from the list. Status, contents and objectives
+     * are kept, and rows that were never synced are left alone.
+     */
+    pub fn follow(&mut self, list: &EditableFile, added: &[String], deleted: &[String]) {
+        let mut rows: Vec<Requirement> = list.requirements.iter().filter_map(|item| {
+            if let Some(row) = self.get(&item.hash) {
+                let mut row = row.clone();
+                row.id = item.id.clone();
+                row.category = item.category.clone();
+                return Some(row);
+            }
+            return added.contains(&item.hash).then(|| item.clone());
+        }).collect();
+        rows.extend(self.requirements.iter()
+            .filter(|x| list.get(&x.hash).is_none() && !deleted.contains(&x.hash))
+            .cloned());
+
+        for row in &rows {
+            self.categories.entry(row.category.to_string()).or_insert(row.category.to_string());
+        }
+        self.requirements = rows;
+        self.is_restructured = true;
+    }
+
+    /// Start and end index of a requirement and its children.
+    fn subtree(&self, hash: &str) -> Option<(usize, usize)> {
+        let start = match self.requirements.iter().position(|x| x.hash == hash) {
+            Some(start) => start,
+            None => {
+                printerror!("Could not find {hash} in {:?}.", self.path);
+                return None;
+            }
+        };
+        let root = &self.requirements[start];
+        let len = self.requirements[start + 1..].iter()
+            .take_while(|x| x.category == root.category && x.id.len() > root.id.len() && x.id.starts_with(&root.id))
+            .count();
+        return Some((start, start + 1 + len));
+    }
+
+    /// Index after the last descendant of parent, or after the last requirement of category.
+    fn insertion_index(&self, category: &str, parent: &[usize]) -> Option<usize> {
+        if !parent.is_empty() && !self.requirements.iter().any(|x| *x.category == category && x.id == parent) {
+            printerror!("Could not find parent {category}.{} in {:?}.",
+                parent.iter().map(|x| x.to_string()).collect::<Vec<String>>().join("."), self.path);
+            return None;
+        }
+        return Some(match self.requirements.iter().rposition(|x| *x.category == category && x.id.starts_with(parent)) {
+            Some(last) => last + 1,
+            // Empty or new category.
+            None => match self.requirements.iter().rposition(|x| *x.category == category) {
+                Some(last) => last + 1,
+                None => self.requirements.len(),
+            }
+        });
+    }
+
+    /// Recalculate ids from each requirement's depth and position, the same way the list is parsed.
+    fn renumber(&mut self) {
+        let mut ids: HashMap<String, Vec<usize>> = HashMap::new();
+        for req in &mut self.requirements {
+            let id = ids.entry(req.category.to_string()).or_default();
+            let depth = req.id.len().max(1);
+            if id.len() >= depth {
+                id.truncate(depth);
+                id[depth - 1] += 1;
+            } else {
+                id.resize(depth, 1);
+            }
+            req.id = id.clone();
+            let num = *id.last().unwrap();
+            req.list_item = match &req.list_item {
+                ListItem::Ordered(_) => ListItem::Ordered(num),
+                ListItem::Hybrid(_, ch) => ListItem::Hybrid(num, *ch),
+                item => item.clone(),
+            };
+        }
+        self.is_restructured = true;
+    }
+
+    /// Rewrite list/csv/md contents from requirements.
+    fn to_text(&self) -> String {
+        if !self.is_restructured {
+            return self.contents.clone();
+        }
+        let mut lines: Vec<String> = Vec::new();
+        match self.kind {
+            FileKind::Csv => lines.push(Requirement::get_csv_header().trim_end().to_string()),
+            FileKind::Markdown => lines.push(Requirement::get_md_header().trim_end().to_string()),
+            _ => (),
+        }
+        let mut category = "";
+        for req in &self.requirements {
+            match self.kind {
+                FileKind::Csv => lines.push(req.to_csv_format().trim_end().to_string()),
+                FileKind::Markdown => lines.push(req.to_md_format().trim_end().to_string()),
+                _ => {
+                    if *req.category != category {
+                        category = &req.category;
+                        lines.push(self.categories.get(category).cloned().unwrap_or(category.to_string()));
+                    }
+                    lines.push(req.to_text_format());
+                }
+            }
+        }
+        // Keep categories that are now empty.
+        if self.kind == FileKind::List {
+            let mut empty: Vec<&String> = self.categories.keys()
+                .filter(|x| !self.requirements.iter().any(|req| *req.category == **x))
+                .collect();
+            empty.sort();
+            lines.extend(empty.into_iter().map(|x| self.categories[x].clone()));
+        }
+        let mut output = lines.join("\n");
+        if self.contents.ends_with('\n') || self.kind != FileKind::List {
+            output += "\n";
+        }
+        return output;
+    }
+
+    /// Add this file to a transaction, converting it back to its original format.
+    pub fn save(&self, transaction: &mut Transaction) -> Option<()> {
+        let contents = match self.kind {
+            FileKind::List | FileKind::Csv | FileKind::Markdown => self.to_text(),
+            FileKind::Toml => to_toml_format(&self.requirements, &self.categories)?,
+            FileKind::Document => {
+                let set = RequirementSet::new(&self.requirements, &self.categories);
+                if self.path.extension().is_some_and(|x| x == "json") { set.to_json()? } else { set.to_yaml()? }
+            },
+            FileKind::Workbook => {
+                return transaction.write_workbook(&self.path, &self.requirements, self.use_sheet_per_category);
+            }
+        };
+        return transaction.write(&self.path, &contents);
+    }
+}
+
+/**
+ * Resolve a requirement from its hash, or its id. Ids may be prefixed with a category
+ * (e.g. PROJ.1.2), which is required if the same id is used in several categories.
+ */
+pub fn find_requirement<'a>(reqs: &'a [Requirement], target: &str) -> Option<&'a Requirement> {
+    return match lookup_requirement(reqs, target) {
+        Ok(req) => Some(req),
+        Err(err) => {
+            printerror!("{err}");
+            None
+        }
+    };
+}
+
+/// Same as find_requirement, but returns why the requirement couldn't be found instead of printing it.
+pub fn lookup_requirement<'a>(reqs: &'a [Requirement], target: &str) -> Result<&'a Requirement, String> {
+    let target = target.trim_start_matches("(@").trim_start_matches('@').trim_end_matches(')');
+    if let Some(req) = reqs.iter().find(|x| x.hash == target) {
+        return Ok(req);
+    }
+
+    let mut parts: Vec<&str> = target.split('.').collect();
+    let category = match parts.first() {
+        Some(x) if x.parse::<usize>().is_err() => Some(parts.remove(0)),
+        _ => None,
+    };
+    let id: Option<Vec<usize>> = parts.iter().map(|x| x.parse::<usize>().ok()).collect();
+    let matches: Vec<&Requirement> = match id {
+        Some(id) if !id.is_empty() => reqs.iter()
+            .filter(|x| x.id == id && category.map_or(true, |c| *x.category == c))
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    return match matches.len() {
+        0 => Err(format!("Could not find a requirement with hash or id \"{target}\".")),
+        1 => Ok(matches[0]),
+        _ => Err(format!("Id \"{target}\" is used in several categories ({}). Prefix it with the category, or use the hash.",
+            matches.iter().map(|x| x.category.as_str()).collect::<Vec<&str>>().join(", "))),
+    };
+}
+
+/**
+ * Resolve requirements from a glob over ids, e.g. PROJ.1.* for the children of PROJ.1. `*` matches
+ * within one level of the id, while `**` matches any number of levels, so PROJ.1.** is all of
+ * PROJ.1's descendants. Patterns starting with a number match every category. Patterns
+ * without wildcards are treated as a single hash or id.
+ */
+pub fn find_requirements<'a>(reqs: &'a [Requirement], pattern: &str) -> Option<Vec<&'a Requirement>> {
+    if !pattern.contains(['*', '?', '[']) {
+        return Some(vec![find_requirement(reqs, pattern)?]);
+    }
+    let mut parts: Vec<&str> = pattern.split('.').collect();
+    if parts[0].parse::<usize>().is_ok() {
+        parts.insert(0, "*");
+    }
+    let glob = match glob::Pattern::new(&parts.join("/")) {
+        Ok(glob) => glob,
+        Err(err) => {
+            printerror!("Invalid pattern \"{pattern}\". {err}");
+            return None;
+        }
+    };
+    let options = glob::MatchOptions { require_literal_separator: true, ..Default::default() };
+    let matches: Vec<&Requirement> = reqs.iter()
+        .filter(|x| glob.matches_with(&format!("{}/{}", x.category, x.id_to_string().replace('.', "/")), options))
+        .collect();
+    if matches.is_empty() {
+        printerror!("No requirements match \"{pattern}\".");
+        return None;
+    }
+    return Some(matches);
+}
+
+/**
+ * Files written by an edit. Each file is first written next to its destination, and only moved
+ * into place once every file was written, so a failed write can't leave the list and spreadsheet
+ * out of sync.
+ */
+pub struct Transaction {
+    pending: Vec<(PathBuf, PathBuf)>,
+}
+
+impl Transaction {
+    pub fn new() -> Transaction {
+        return Transaction { pending: Vec::new() };
+    }
+
+    /// path/to/reqs.csv -> path/to/.ody-edit.reqs.csv. Keeps the extension, so workbooks are
+    /// written in the right format.
+    fn temp_path(path: &PathBuf) -> PathBuf {
+        let name = path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        return path.with_file_name(format!(".ody-edit.{name}"));
+    }
+
+    pub fn write(&mut self, path: &PathBuf, contents: &str) -> Option<()> {
+        let temp = Transaction::temp_path(path);
+        if let Err(err) = fs::write(&temp, contents) {
+            printerror!("Could not write {path:?}. {err}");
+            return None;
+        }
+        self.pending.push((temp, path.clone()));
+        return Some(());
+    }
+
+    pub fn write_workbook(&mut self, path: &PathBuf, reqs: &[Requirement], use_sheet_per_category: bool) -> Option<()> {
+        let temp = Transaction::temp_path(path);
+        write_workbook(&temp, reqs, use_sheet_per_category)?;
+        self.pending.push((temp, path.clone()));
+        return Some(());
+    }
+
+    /// path/to/reqs.csv -> path/to/.ody-backup.reqs.csv
+    fn backup_path(path: &PathBuf) -> PathBuf {
+        let name = path.file_name().map(|x| x.to_string_lossy().to_string()).unwrap_or_default();
+        return path.with_file_name(format!(".ody-backup.{name}"));
+    }
+
+    /**
+     * Replace each file with its new version. Originals are moved aside first, so if a file can't be
+     * replaced, the ones replaced before it are restored and every file is left as it was.
+     */
+    pub fn commit(mut self) -> Option<()> {
+        // Replaced files, with their backup if they existed before.
+        let mut replaced: Vec<(PathBuf, Option<PathBuf>)> = Vec::new();
+        let mut error: Option<String> = None;
+        for (temp, path) in &self.pending {
+            let backup = path.exists().then(|| Transaction::backup_path(path));
+            if let Some(backup) = &backup {
+                if let Err(err) = fs::rename(path, backup) {
+                    error = Some(format!("Could not back up {path:?}. {err}"));
+                    break;
+                }
+            }
+            if let Err(err) = fs::rename(temp, path) {
+                if let Some(backup) = &backup {
+                    let _ = fs::rename(backup, path);
+                }
+                error = Some(format!("Could not overwrite {path:?}. {err}"));
+                break;
+            }
+            replaced.push((path.clone(), backup));
+        }
+
+        if let Some(error) = error {
+            for (path, backup) in replaced.iter().rev() {
+                let restored = match backup {
+                    Some(backup) => fs::rename(backup, path),
+                    None => fs::remove_file(path),
+                };
+                if let Err(err) = restored {
+                    printerror!("Could not restore {path:?}. {err}");
+                }
+            }
+            printerror!("{error}");
+            // Drop removes the temp files that are left.
+            return None;
+        }
+        for backup in replaced.iter().filter_map(|(_, x)| x.as_ref()) {
+            let _ = fs::remove_file(backup);
+        }
+        self.pending.clear();
+        return Some(());
+    }
+}
+
+impl Drop for Transaction {
+    /// Clean up after a transaction that was never committed.
+    fn drop(&mut self) {
+        for (temp, _) in &self.pending {
+            let _ = fs::remove_file(temp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requirements::HashAlgorithm;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("odysseus-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        return dir;
+    }
+
+    #[test]
+    fn set_status_in_place() {
+        let dir = temp_dir("set-status");
+        let list_path = dir.join("reqs.md");
+        let sheet_path = dir.join("reqs.csv");
+        let list = "Category (CAT)\n1. First(@h1)\n\t- [ ] Second (@h2)  \n\n2. [x] Third(@h3)\n";
+        let sheet = "Hash,Category,Id,Contents,Status\nh1,CAT,1,First,0\nh2,CAT,1.1,Second,0\nh3,CAT,2,Third,1\n";
+        fs::write(&list_path, list).unwrap();
+        fs::write(&sheet_path, sheet).unwrap();
+
+        let mut list_file = EditableFile::open(&list_path, false).unwrap();
+        let mut sheet_file = EditableFile::open(&sheet_path, false).unwrap();
+        assert_eq!(sheet_file.kind, FileKind::Csv);
+
+        let hash = find_requirement(&list_file.requirements, "CAT.1.1").unwrap().hash.clone();
+        assert_eq!(hash, "h2");
+        list_file.set_status(&hash, 1).unwrap();
+        sheet_file.set_status(&hash, 1).unwrap();
+        list_file.set_status("h3", 0).unwrap();
+        sheet_file.set_status("h3", 0).unwrap();
+        assert!(list_file.set_status("missing", 1).is_none());
+
+        let mut transaction = Transaction::new();
+        list_file.save(&mut transaction).unwrap();
+        sheet_file.save(&mut transaction).unwrap();
+        transaction.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&list_path).unwrap(), "Category (CAT)\n1. First(@h1)\n\t- [x] Second (@h2)  \n\n2. [ ] Third(@h3)\n");
+        assert_eq!(fs::read_to_string(&sheet_path).unwrap(), "Hash,Category,Id,Contents,Status\nh1,CAT,1,First,0\nh2,CAT,1.1,Second,1\nh3,CAT,2,Third,0\n");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn set_status_keeps_headers() {
+        let dir = temp_dir("set-status-headers");
+        let path = dir.join("reqs.md");
+        fs::write(&path, "Category (CAT)\na. First(@h1)\nb.   [ ] Second(@h2)\n* Third(@h3)\n+ [ ] Fourth(@h4)\n").unwrap();
+        let mut file = EditableFile::open(&path, false).unwrap();
+        for hash in ["h1", "h2", "h3", "h4"] {
+            file.set_status(hash, 1).unwrap();
+        }
+        let mut transaction = Transaction::new();
+        file.save(&mut transaction).unwrap();
+        transaction.commit().unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents, "Category (CAT)\na. [x] First(@h1)\nb.   [x] Second(@h2)\n* [x] Third(@h3)\n+ [x] Fourth(@h4)\n");
+        let (reqs, _) = parse_requirements_str(&contents, false).unwrap();
+        assert!(reqs.iter().all(|x| x.status == 1));
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn failed_commit_restores_files() {
+        let dir = temp_dir("failed-commit");
+        let list_path = dir.join("reqs.md");
+        let sheet_path = dir.join("reqs.csv");
+        fs::write(&list_path, "old list").unwrap();
+        fs::write(&sheet_path, "old sheet").unwrap();
+
+        let mut transaction = Transaction::new();
+        transaction.write(&list_path, "new list").unwrap();
+        transaction.write(&dir.join("new.csv"), "new file").unwrap();
+        transaction.write(&sheet_path, "new sheet").unwrap();
+        // The sheet's new version disappears before it can replace the old one.
+        fs::remove_file(Transaction::temp_path(&sheet_path)).unwrap();
+        assert!(transaction.commit().is_none());
+
+        assert_eq!(fs::read_to_string(&list_path).unwrap(), "old list");
+        assert_eq!(fs::read_to_string(&sheet_path).unwrap(), "old sheet");
+        // No new file, temp files or backups are left behind.
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn add_move_delete() {
+        let dir = temp_dir("restructure");
+        let list_path = dir.join("reqs.md");
+        let sheet_path = dir.join("reqs.csv");
+        fs::write(&list_path, "Project (PROJ)\n1. A(@a)\n\t1. [ ] A1(@a1)\n2. B(@b)\nDocs (DOC)\n- [ ] D(@d)\n").unwrap();
+        fs::write(&sheet_path, "Hash,Category,Id,Contents,Status\na,PROJ,1,A,0\na1,PROJ,1.1,A1 (edited),1\nb,PROJ,2,B,0\nd,DOC,1,D,0\n").unwrap();
+        let mut list = EditableFile::open(&list_path, false).unwrap();
+        let mut sheet = EditableFile::open(&sheet_path, false).unwrap();
+
+        let added = list.add("PROJ", &[1], "A2").unwrap();
+        assert!(list.add("PROJ", &[3], "Missing parent").is_none());
+        list.move_to("b", "DOC", &[]).unwrap();
+        assert!(list.move_to("a", "PROJ", &[1, 1]).is_none());
+        let deleted = list.delete("d").unwrap();
+        assert_eq!(deleted, vec!["d"]);
+        sheet.follow(&list, &[added.clone()], &deleted);
+
+        let mut transaction = Transaction::new();
+        list.save(&mut transaction).unwrap();
+        sheet.save(&mut transaction).unwrap();
+        transaction.commit().unwrap();
+
+        assert_eq!(fs::read_to_string(&list_path).unwrap(),
+            format!("Project (PROJ)\n1. A(@a)\n\t1. [ ] A1(@a1)\n\t2. [ ] A2(@{added})\nDocs (DOC)\n1. B(@b)\n"));
+        // Status and contents are kept from the spreadsheet.
+        assert_eq!(fs::read_to_string(&sheet_path).unwrap(),
+            format!("Hash,Category,Id,Contents,Status,Objective,Created,Completed,Trace\na,PROJ,1,A,0,,,,\na1,PROJ,1.1,A1 (edited),1,,,,\n{added},PROJ,1.2,A2,0,,,,\nb,DOC,1,B,0,,,,\n"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn add_existing_hash() {
+        let dir = temp_dir("add-existing-hash");
+        let path = dir.join("reqs.md");
+        let fnv1a_builder = || RequirementBuilder::with_hash_algorithm(HashAlgorithm::Fnv1a);
+        let taken = fnv1a_builder().build("Foo".to_string(), vec![], Rc::new("PROJ".to_string()), ListItem::Unordered).hash;
+        fs::write(&path, format!("Project (PROJ)\n1. Foo(@{taken})\n")).unwrap();
+        let mut list = EditableFile::open(&path, false).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+
+        // The first fnv1a hash is taken, and the retry is numbered.
+        let added = list.add_with(&mut fnv1a_builder(), "PROJ", &[], "Foo").unwrap();
+        assert_ne!(added, taken);
+        assert!(list.add("PROJ", &[], &format!("Bar (@{taken})")).is_none());
+        assert!(list.add("PROJ", &[], "Bar (@other)").is_none());
+        assert_eq!(list.requirements.len(), 2);
+    }
+    #[test]
+    fn set_objective_in_place() {
+        let dir = temp_dir("set-objective");
+        let path = dir.join("reqs.csv.md");
+        fs::write(&path, "|Hash|Category|Id|Contents|Status|\n|---|---|---|---|---|\n|h1|CAT|1|First|0|\n|h2|CAT|2|Second|0|\n").unwrap();
+        let mut file = EditableFile::open(&path, false).unwrap();
+        assert_eq!(file.kind, FileKind::Markdown);
+        file.set_objective("h2", Some("1.0.0")).unwrap();
+        file.set_completed_at("h1", DateTime::parse_from_rfc3339("2026-10-18T12:00:00+02:00").ok()).unwrap();
+
+        let mut transaction = Transaction::new();
+        file.save(&mut transaction).unwrap();
+        transaction.commit().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(),
+            "|Hash|Category|Id|Contents|Status|Objective|Created|Completed|\n|---|---|---|---|---|---|---|---|\n|h1|CAT|1|First|0|||2026-10-18T12:00:00+02:00|\n|h2|CAT|2|Second|0|1.0.0|\n");
+        let _ = fs::remove_dir_all(&dir);
+    }
+    #[test]
+    fn find_by_glob() {
+        let (reqs, _) = parse_requirements_str("A (A)\n1. One(@h1)\n2. Two(@h2)\n\t1. Three(@h3)\n\t\t1. Four(@h4)\nB (B)\n1. One(@h5)\n\t1. Two(@h6)\n", false).unwrap();
+        let hashes = |pattern: &str| find_requirements(&reqs, pattern).map(|x| x.iter().map(|x| x.hash.as_str()).collect::<Vec<&str>>());
+        assert_eq!(hashes("A.2.*"), Some(vec!["h3"]));
+        assert_eq!(hashes("A.2.**"), Some(vec!["h3", "h4"]));
+        assert_eq!(hashes("A.*"), Some(vec!["h1", "h2"]));
+        assert_eq!(hashes("1.*"), Some(vec!["h6"]));
+        assert_eq!(hashes("B.1"), Some(vec!["h5"]));
+        assert_eq!(hashes("C.*"), None);
+    }
+    #[test]
+    fn find_by_hash_or_id() {
+        let (reqs, _) = parse_requirements_str("A (A)\n1. One(@h1)\nB (B)\n1. One(@h2)\n\t1. Two(@h3)\n", false).unwrap();
+        assert_eq!(find_requirement(&reqs, "@h1").unwrap().hash, "h1");
+        assert_eq!(find_requirement(&reqs, "1.1").unwrap().hash, "h3");
+        assert_eq!(find_requirement(&reqs, "B.1").unwrap().hash, "h2");
+        assert!(find_requirement(&reqs, "1").is_none());
+        assert!(find_requirement(&reqs, "C.1").is_none());
+    }
+}
@@ -0,0 +1,234 @@
+use std::{collections::HashMap, fmt::Display, fs::{self, OpenOptions}, io::Write, path::PathBuf, str::FromStr};
+use chrono::{DateTime, FixedOffset, SecondsFormat};
+use mythos_core::printerror;
+
+use super::Requirement;
+
+const HISTORY_HEADER: &str = "Hash,Old,New,Timestamp,Source\n";
+
+/// Where a status change was made.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatusSource { List, Sheet, Cli, Tests }
+
+/// One line of a spreadsheet's history. Old is None when the requirement was first seen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StatusChange {
+    pub hash: String,
+    pub old: Option<u8>,
+    pub new: u8,
+    pub timestamp: DateTime<FixedOffset>,
+    pub source: StatusSource,
+}
+
+impl Display for StatusSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return write!(f, "{}", match self {
+            StatusSource::List => "list",
+            StatusSource::Sheet => "sheet",
+            StatusSource::Cli => "cli",
+            StatusSource::Tests => "tests",
+        });
+    }
+}
+
+impl FromStr for StatusSource {
+    type Err = ();
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        return match value {
+            "list" => Ok(StatusSource::List),
+            "sheet" => Ok(StatusSource::Sheet),
+            "cli" => Ok(StatusSource::Cli),
+            "tests" => Ok(StatusSource::Tests),
+            _ => Err(()),
+        };
+    }
+}
+
+impl StatusChange {
+    pub fn new(hash: &str, old: Option<u8>, new: u8, timestamp: DateTime<FixedOffset>, source: StatusSource) -> StatusChange {
+        return StatusChange { hash: hash.to_string(), old, new, timestamp, source };
+    }
+
+    pub fn to_csv_format(&self) -> String {
+        let old = self.old.map(|x| x.to_string()).unwrap_or_default();
+        return format!("{},{old},{},{},{}\n", self.hash, self.new, format_timestamp(Some(&self.timestamp)), self.source);
+    }
+
+    fn parse(line: &str) -> Option<StatusChange> {
+        let values: Vec<&str> = line.split(',').map(|x| x.trim()).collect();
+        let [hash, old, new, timestamp, source] = values.as_slice() else { return None };
+        return Some(StatusChange {
+            hash: hash.to_string(),
+            old: if old.is_empty() { None } else { Some(old.parse().ok()?) },
+            new: new.parse().ok()?,
+            timestamp: DateTime::parse_from_rfc3339(timestamp).ok()?,
+            source: source.parse().ok()?,
+        });
+    }
+}
+
+/// RFC 3339, to the second. Empty if there is no timestamp.
+pub fn format_timestamp(timestamp: Option<&DateTime<FixedOffset>>) -> String {
+    return timestamp.map(|x| x.to_rfc3339_opts(SecondsFormat::Secs, false)).unwrap_or_default();
+}
+
+/// E.g. path/to/reqs.csv -> path/to/.reqs.csv.history
+pub fn history_path(spreadsheet: &PathBuf) -> PathBuf {
+    let mut file_name = std::ffi::OsString::from(".");
+    file_name.push(spreadsheet.file_name().unwrap_or_default());
+    file_name.push(".history");
+    return spreadsheet.with_file_name(file_name);
+}
+
+/// Read a history file. A missing file is an empty history.
+pub fn read_history(path: &PathBuf) -> Option<Vec<StatusChange>> {
+    if !path.exists() {
+        return Some(Vec::new());
+    }
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            printerror!("Could not read history file {path:?}. {err}");
+            return None;
+        }
+    };
+    let mut output: Vec<StatusChange> = Vec::new();
+    for (i, line) in contents.lines().enumerate().skip(1).filter(|(_, x)| !x.trim().is_empty()) {
+        match StatusChange::parse(line) {
+            Some(change) => output.push(change),
+            None => {
+                printerror!("Could not parse line {} of history file {path:?}. Line contents: \"{line}\"", i + 1);
+                return None;
+            }
+        }
+    }
+    return Some(output);
+}
+
+/// Add changes to the end of a history file, creating it if needed. Existing lines are never rewritten.
+pub fn append_history(path: &PathBuf, changes: &[StatusChange]) -> Option<()> {
+    if changes.is_empty() {
+        return Some(());
+    }
+    let mut contents = if path.exists() { String::new() } else { HISTORY_HEADER.to_string() };
+    for change in changes {
+        contents += &change.to_csv_format();
+    }
+    let result = OpenOptions::new().create(true).append(true).open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()));
+    if let Err(err) = result {
+        printerror!("Could not write history file {path:?}. {err}");
+        return None;
+    }
+    return Some(());
+}
+
+/**
+ * Status changes made since the last sync. The spreadsheet's status is compared to the snapshot of the last
+ * sync (if there is one) to find edits made in the sheet, and the merged status to the spreadsheet's to find
+ * the rest, which came from the list. Requirements that aren't in the spreadsheet are recorded as new.
+ * Sheet edits already in the history (e.g. from ody set-status) aren't recorded again.
+ */
+pub fn detect_status_changes(merged: &[Requirement], sheet: &HashMap<String, Requirement>, base: Option<&HashMap<String, Requirement>>, history: &[StatusChange], now: DateTime<FixedOffset>) -> Vec<StatusChange> {
+    let recorded: HashMap<&str, u8> = history.iter().map(|x| (x.hash.as_str(), x.new)).collect();
+    let mut changes: Vec<StatusChange> = Vec::new();
+    for req in merged {
+        let Some(sheet_req) = sheet.get(&req.hash) else {
+            changes.push(StatusChange::new(&req.hash, None, req.status, now, StatusSource::List));
+            continue;
+        };
+        if let Some(base_req) = base.and_then(|x| x.get(&req.hash)) {
+            if base_req.status != sheet_req.status && recorded.get(req.hash.as_str()) != Some(&sheet_req.status) {
+                changes.push(StatusChange::new(&req.hash, Some(base_req.status), sheet_req.status, now, StatusSource::Sheet));
+            }
+        }
+        if req.status != sheet_req.status {
+            changes.push(StatusChange::new(&req.hash, Some(sheet_req.status), req.status, now, StatusSource::List));
+        }
+    }
+    return changes;
+}
+
+/**
+ * Fill in created_at (the first change recorded) and completed_at (the last time the requirement was
+ * marked done, if it still is) from a history. Requirements with no history are left without either.
+ */
+pub fn apply_history(reqs: &mut [Requirement], history: &[StatusChange]) {
+    let mut timestamps: HashMap<&str, (DateTime<FixedOffset>, Option<DateTime<FixedOffset>>)> = HashMap::new();
+    for change in history {
+        let (_, completed_at) = timestamps.entry(&change.hash).or_insert((change.timestamp, None));
+        if change.new != 1 {
+            *completed_at = None;
+        } else if change.old != Some(1) {
+            *completed_at = Some(change.timestamp);
+        }
+    }
+    for req in reqs {
+        let (created_at, completed_at) = match timestamps.get(req.hash.as_str()) {
+            Some((created_at, completed_at)) => (Some(*created_at), *completed_at),
+            None => (None, None),
+        };
+        req.created_at = created_at;
+        req.completed_at = completed_at.filter(|_| req.is_complete());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(value: &str) -> DateTime<FixedOffset> {
+        return DateTime::parse_from_rfc3339(value).unwrap();
+    }
+
+    #[test]
+    fn detect_changes() {
+        let now = time("2026-10-18T12:00:00+02:00");
+        let to_map = |reqs: Vec<Requirement>| reqs.into_iter().map(|x| (x.hash.clone(), x)).collect::<HashMap<String, Requirement>>();
+        let base = to_map(vec![Requirement::test("h1", 0), Requirement::test("h2", 0), Requirement::test("h3", 0), Requirement::test("h5", 0)]);
+        // h1 was marked done in the sheet, h2 in the list, h4 is new and h5 was done from the command line.
+        let sheet = to_map(vec![Requirement::test("h1", 1), Requirement::test("h2", 0), Requirement::test("h3", 0), Requirement::test("h5", 1)]);
+        let merged = vec![Requirement::test("h1", 1), Requirement::test("h2", 1), Requirement::test("h3", 0), Requirement::test("h4", 0), Requirement::test("h5", 1)];
+        let history = vec![StatusChange::new("h5", Some(0), 1, now, StatusSource::Cli)];
+
+        let changes = detect_status_changes(&merged, &sheet, Some(&base), &history, now);
+        assert_eq!(changes, vec![
+            StatusChange::new("h1", Some(0), 1, now, StatusSource::Sheet),
+            StatusChange::new("h2", Some(0), 1, now, StatusSource::List),
+            StatusChange::new("h4", None, 0, now, StatusSource::List),
+        ]);
+    }
+    #[test]
+    fn history_round_trip() {
+        let path = std::env::temp_dir().join(format!(".odysseus-{}.csv.history", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let changes = vec![
+            StatusChange::new("h1", None, 0, time("2026-10-01T09:00:00+02:00"), StatusSource::List),
+            StatusChange::new("h1", Some(0), 1, time("2026-10-02T09:00:00+02:00"), StatusSource::Cli),
+        ];
+        append_history(&path, &changes[..1]).unwrap();
+        append_history(&path, &changes[1..]).unwrap();
+        let history = read_history(&path).unwrap();
+        let _ = fs::remove_file(&path);
+        assert_eq!(history, changes);
+        assert_eq!(history_path(&PathBuf::from("path/to/reqs.csv")), PathBuf::from("path/to/.reqs.csv.history"));
+    }
+    #[test]
+    fn derive_timestamps() {
+        let history = vec![
+            StatusChange::new("h1", None, 0, time("2026-10-01T09:00:00+02:00"), StatusSource::List),
+            StatusChange::new("h1", Some(0), 1, time("2026-10-02T09:00:00+02:00"), StatusSource::Sheet),
+            StatusChange::new("h2", Some(0), 1, time("2026-10-03T09:00:00+02:00"), StatusSource::Cli),
+            StatusChange::new("h2", Some(1), 0, time("2026-10-04T09:00:00+02:00"), StatusSource::Cli),
+        ];
+        let mut reqs = vec![Requirement::test("h1", 1), Requirement::test("h2", 0), Requirement::test("h3", 1)];
+        apply_history(&mut reqs, &history);
+        assert_eq!(reqs[0].created_at, Some(time("2026-10-01T09:00:00+02:00")));
+        assert_eq!(reqs[0].completed_at, Some(time("2026-10-02T09:00:00+02:00")));
+        assert_eq!(reqs[1].created_at, Some(time("2026-10-03T09:00:00+02:00")));
+        assert_eq!(reqs[1].completed_at, None);
+        assert_eq!(reqs[2].created_at, None);
+        assert_eq!(reqs[0].to_csv_format(), "h1,CAT,1,,1,,2026-10-01T09:00:00+02:00,2026-10-02T09:00:00+02:00,\n");
+    }
+}
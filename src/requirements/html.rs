@@ -0,0 +1,123 @@
+use super::{Progress, ProgressReport, RequirementSet};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em auto; max-width: 60em; color: #222; }
+h1 { margin-bottom: 0.2em; }
+#filter { width: 100%; padding: 0.5em; margin: 1em 0; font-size: 1em; box-sizing: border-box; }
+ul { list-style: none; padding-left: 0; }
+li { padding: 0.2em 0; }
+.id { font-family: monospace; color: #555; margin-right: 0.5em; }
+.hash { font-family: monospace; font-size: 0.8em; color: #999; margin-left: 0.5em; }
+.badge { display: inline-block; min-width: 3em; text-align: center; border-radius: 0.8em; padding: 0 0.5em; margin-right: 0.5em; font-size: 0.8em; color: #fff; background: #d08c00; }
+.badge.open { background: #888; }
+.badge.done { background: #2a9d3a; }
+.bar { position: relative; background: #e4e4e4; border-radius: 0.3em; height: 1.4em; margin: 0.3em 0 0.8em; }
+.fill { background: #2a9d3a; border-radius: 0.3em; height: 100%; }
+.bar span { position: absolute; left: 0.5em; top: 0.1em; font-size: 0.85em; }
+.label { font-weight: bold; }
+";
+
+const SCRIPT: &str = "
+function filterRows(query) {
+    query = query.toLowerCase();
+    for (const section of document.querySelectorAll('section.category')) {
+        let visible = 0;
+        for (const row of section.querySelectorAll('li.req')) {
+            const show = row.dataset.search.includes(query);
+            row.style.display = show ? '' : 'none';
+            visible += show ? 1 : 0;
+        }
+        section.style.display = visible > 0 ? '' : 'none';
+    }
+}
+";
+
+impl RequirementSet {
+    /// Render a self-contained html page showing the progress of each category and objective.
+    pub fn to_html_report(&self, title: &str) -> String {
+        let report = ProgressReport::new(self);
+        let mut body = format!("<h1>{}</h1>\n", escape_html(title));
+        body += &progress_bar("Total", &report.total);
+        body += "<input id=\"filter\" type=\"search\" placeholder=\"Filter requirements...\" oninput=\"filterRows(this.value)\">\n";
+
+        if !report.objectives.is_empty() {
+            body += "<section class=\"objectives\">\n<h2>Objectives</h2>\n";
+            for objective in &report.objectives {
+                body += &progress_bar(&objective.name, &objective.progress);
+            }
+            body += "</section>\n";
+        }
+
+        for category in &report.categories {
+            let items = self.requirements.iter().filter(|x| *x.category == category.group.key);
+
+            body += &format!("<section class=\"category\">\n<h2>{}</h2>\n", escape_html(&category.group.name));
+            body += &progress_bar("Complete", &category.group.progress);
+            body += "<ul>\n";
+            for req in items {
+                let search = format!("{} {} {} {} {} {}",
+                    req.category, req.id_to_string(), req.hash, req.contents, req.status_label(), req.objective.as_deref().unwrap_or(""))
+                    .to_lowercase();
+                let class = match req.status { 0 => "open", 1 => "done", _ => "other" };
+                body += &format!("<li class=\"req\" style=\"margin-left: {indent}em\" data-search=\"{search}\"><span class=\"id\">{id}</span><span class=\"badge {class}\">{status}</span>{contents}<span class=\"hash\">@{hash}</span></li>\n",
+                    indent=(req.id.len().max(1) - 1) as f32 * 1.5,
+                    search=escape_html(&search),
+                    id=req.id_to_string(),
+                    class=class,
+                    status=escape_html(&req.status_label()),
+                    contents=escape_html(&req.contents),
+                    hash=escape_html(&req.hash));
+            }
+            body += "</ul>\n</section>\n";
+        }
+
+        return format!("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n{body}<script>{SCRIPT}</script>\n</body>\n</html>\n",
+            title=escape_html(title));
+    }
+}
+
+fn progress_bar(label: &str, progress: &Progress) -> String {
+    return format!("<div class=\"label\">{label}</div><div class=\"bar\"><div class=\"fill\" style=\"width: {percent:.0}%\"></div><span>{percent:.0}% ({done}/{total})</span></div>\n",
+        label=escape_html(label),
+        percent=progress.percent(),
+        done=progress.done,
+        total=progress.total);
+}
+
+fn escape_html(value: &str) -> String {
+    return value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use super::*;
+    use crate::requirements::parse_requirements;
+
+    #[test]
+    fn render_html_report() {
+        let (reqs, categories) = parse_requirements(&PathBuf::from("tests/test_compare.txt"), false).unwrap();
+        let html = RequirementSet::new(&reqs, &categories).to_html_report("Test <report>");
+
+        assert!(html.contains("<title>Test &lt;report&gt;</title>"));
+        // Of h1, h2 and h3, only h3 is done.
+        assert!(html.contains("<span>33% (1/3)</span>"));
+        assert!(html.contains("style=\"margin-left: 1.5em\""));
+        assert!(html.contains("<span class=\"badge done\">done</span>ASDF"));
+        // No external assets.
+        assert!(!html.contains("src=") && !html.contains("href="));
+    }
+    #[test]
+    fn render_objectives() {
+        let (mut reqs, _) = parse_requirements(&PathBuf::from("tests/test_compare.txt"), false).unwrap();
+        reqs[0].objective = Some("1.0.0".to_string());
+        let html = RequirementSet::new(&reqs, &HashMap::new()).to_html_report("");
+        assert!(html.contains("<h2>Objectives</h2>\n<div class=\"label\">1.0.0</div>"));
+    }
+}
@@ -5,6 +5,7 @@ use super::{ListItem, ListParser};
  * Parses out the following types of markdown style lists:
  * - [ ]
  * - [x]
+ * * [ ] and + [ ]
  * 1. 
  * 1. [ ]
  * 1. [x]
@@ -13,7 +14,7 @@ use super::{ListItem, ListParser};
  * *
  * +
  */
-const TODO_HEADER: &str = r"(?<todo>- \[(?<todo_mark>.)])";
+const TODO_HEADER: &str = r"(?<todo>[-+*] \[(?<todo_mark>.)])";
 const ORDERED_HEADER: &str = r"(?<ordered>(?<number>[0-9]+)\.|(?<letter>[a-zA-Z])\.)";
 const UNORDERED_HEADER: &str = r"(?<unordered>[-+*])";
 const HYBRID_HEADER: &str = r"(?<hybrid>((?<hnumber>[0-9]+)|(?<hletter>[a-zA-Z]))\.\s+\[(?<htodo_mark>.)])";
@@ -102,6 +103,10 @@ mod tests {
         let res = parser.parse("- [.] asdf").unwrap();
         assert!(matches!(res.0, ListItem::Todo('.')));
         assert_eq!(res.1, "asdf");
+
+        let res = parser.parse("* [x] asdf").unwrap();
+        assert!(matches!(res.0, ListItem::Todo('x')));
+        assert_eq!(res.1, "asdf");
     }
 
     #[test]
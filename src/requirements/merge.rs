@@ -0,0 +1,306 @@
+use std::{collections::HashMap, ffi::OsString, fmt::Display, path::PathBuf};
+use mythos_core::printinfo;
+
+use super::{Authority, MergeConflict, MergeField, MergeRules, Requirement};
+
+/// Which version of a field should be kept.
+#[derive(Debug, PartialEq)]
+enum Pick { List, Sheet, Conflict }
+
+/// Path of the file holding the last synced state of a spreadsheet.
+/// E.g. path/to/reqs.csv -> path/to/.reqs.csv.snapshot
+pub fn snapshot_path(spreadsheet: &PathBuf) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(spreadsheet.file_name().unwrap_or(&OsString::from("requirements.csv")));
+    file_name.push(".snapshot");
+    return spreadsheet.with_file_name(file_name);
+}
+
+impl Authority {
+    pub fn parse(value: &str) -> Option<Authority> {
+        return match value {
+            "list" => Some(Authority::List),
+            "sheet" => Some(Authority::Sheet),
+            "newest" => Some(Authority::Newest),
+            _ => None
+        };
+    }
+}
+
+impl MergeRules {
+    /// Resolve Authority::Newest to the side that was modified last.
+    fn winner(&self, authority: Authority) -> Authority {
+        return match authority {
+            Authority::Newest if self.list_is_newer => Authority::List,
+            Authority::Newest => Authority::Sheet,
+            _ => authority
+        };
+    }
+}
+
+impl Default for MergeRules {
+    /// The spreadsheet is the authority on status, while the list is the authority on everything else.
+    fn default() -> Self {
+        return MergeRules {
+            status_from: Authority::Sheet,
+            contents_from: Authority::List,
+            list_is_newer: false,
+        };
+    }
+}
+
+/**
+ * Combine the list file with the spreadsheet.
+ *
+ * Without a snapshot of the last sync, any contents or status that differ are taken from the
+ * file given by the rules.
+ *
+ * With a snapshot, contents and status are merged three ways: a field changed on only one side
+ * takes that side's value. A field changed on both sides is a conflict, which is reported and
+ * resolved using the rules. Id and category are always taken from the list, since they are
+ * defined by its structure.
+ */
+pub fn merge_requirements(list: Vec<Requirement>, sheet: &HashMap<String, Requirement>, base: Option<&HashMap<String, Requirement>>, rules: &MergeRules, be_verbose: bool) -> (Vec<Requirement>, Vec<MergeConflict>) {
+    let mut output: Vec<Requirement> = Vec::with_capacity(list.len());
+    let mut conflicts: Vec<MergeConflict> = Vec::new();
+
+    for mut req in list {
+        printinfo!(be_verbose, "READ TXT: {}", req.to_text_format());
+        let prev = base.and_then(|base| base.get(&req.hash));
+
+        match (sheet.get(&req.hash), prev) {
+            (Some(val), prev) => {
+                printinfo!(be_verbose, "COMPARE TO CSV: {}", val.to_csv_format().trim_end());
+                req.merge_from(val, prev, rules, &mut conflicts, be_verbose);
+            },
+            // Row was removed from the spreadsheet since the last sync.
+            (None, Some(prev)) => {
+                if req.contents == prev.contents && req.status == prev.status {
+                    printinfo!(be_verbose, "DELETED FROM CSV: {}", req.to_csv_format().trim_end());
+                    continue;
+                }
+                conflicts.push(MergeConflict {
+                    hash: req.hash.clone(),
+                    field: MergeField::Existence,
+                    base: "exists".to_string(),
+                    list: "edited".to_string(),
+                    sheet: "deleted".to_string(),
+                    resolution: Authority::List,
+                });
+            },
+            (None, None) => (),
+        }
+        output.push(req);
+    }
+
+    // Rows added to the spreadsheet have no place in the list file, so they cannot be kept.
+    // Rows missing from the list are deleted, as before.
+    if let Some(base) = base {
+        let mut added: Vec<&Requirement> = sheet.values()
+            .filter(|x| !base.contains_key(&x.hash) && !output.iter().any(|y| y.hash == x.hash))
+            .collect();
+        added.sort_by(|a, b| (&a.category, &a.id).cmp(&(&b.category, &b.id)));
+        for req in added {
+            conflicts.push(MergeConflict {
+                hash: req.hash.clone(),
+                field: MergeField::Existence,
+                base: "missing".to_string(),
+                list: "missing".to_string(),
+                sheet: "added".to_string(),
+                resolution: Authority::List,
+            });
+        }
+    }
+
+    return (output, conflicts);
+}
+
+impl Requirement {
+    /// Merge this (list) requirement with its spreadsheet version, and last synced version if there is one.
+    fn merge_from(&mut self, sheet: &Requirement, base: Option<&Requirement>, rules: &MergeRules, conflicts: &mut Vec<MergeConflict>, be_verbose: bool) {
+        let contents = match base {
+            Some(base) => pick(&base.contents, &self.contents, &sheet.contents),
+            None => pick_without_base(&self.contents, &sheet.contents, rules.contents_from, rules),
+        };
+        let contents = match contents {
+            Pick::Conflict => {
+                let resolution = rules.winner(rules.contents_from);
+                conflicts.push(MergeConflict {
+                    hash: self.hash.clone(),
+                    field: MergeField::Contents,
+                    // Unwrap is safe, there are only conflicts if there is a base.
+                    base: base.unwrap().contents.clone(),
+                    list: self.contents.clone(),
+                    sheet: sheet.contents.clone(),
+                    resolution,
+                });
+                if resolution == Authority::List { Pick::List } else { Pick::Sheet }
+            },
+            pick => pick
+        };
+        if contents == Pick::Sheet {
+            printinfo!(be_verbose, "Overwriting contents with value from csv file: {} -> {}.", self.contents, sheet.contents);
+            self.contents = sheet.contents.clone();
+        }
+
+        let status = match base {
+            Some(base) => pick(&base.status, &self.status, &sheet.status),
+            None => pick_without_base(&self.status, &sheet.status, rules.status_from, rules),
+        };
+        let status = match status {
+            Pick::Conflict => {
+                let resolution = rules.winner(rules.status_from);
+                conflicts.push(MergeConflict {
+                    hash: self.hash.clone(),
+                    field: MergeField::Status,
+                    base: base.unwrap().status.to_string(),
+                    list: self.status.to_string(),
+                    sheet: sheet.status.to_string(),
+                    resolution,
+                });
+                if resolution == Authority::List { Pick::List } else { Pick::Sheet }
+            },
+            pick => pick
+        };
+        if status == Pick::Sheet {
+            self.copy_status(sheet, be_verbose);
+        }
+
+        // Objectives are managed from the spreadsheet, so an empty cell unassigns the requirement.
+        self.objective = sheet.objective.clone();
+        // Only written by ody trace, so the spreadsheet always has the latest.
+        self.trace = sheet.trace.clone();
+    }
+}
+
+/// Without a previous sync to compare against, any difference is settled by authority.
+fn pick_without_base<T: PartialEq>(list: &T, sheet: &T, authority: Authority, rules: &MergeRules) -> Pick {
+    if list == sheet || rules.winner(authority) == Authority::List {
+        return Pick::List;
+    }
+    return Pick::Sheet;
+}
+
+fn pick<T: PartialEq>(base: &T, list: &T, sheet: &T) -> Pick {
+    if list == sheet || sheet == base {
+        return Pick::List;
+    }
+    if list == base {
+        return Pick::Sheet;
+    }
+    return Pick::Conflict;
+}
+
+impl Display for MergeConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let resolution = match (&self.field, self.resolution) {
+            (MergeField::Existence, _) if self.sheet == "added" => "Add it to the list file to keep it.",
+            (_, Authority::Sheet) => "Keeping spreadsheet value.",
+            _ => "Keeping list value.",
+        };
+        return write!(f, "Conflict on {hash} ({field:?}). Last sync: \"{base}\", list: \"{list}\", spreadsheet: \"{sheet}\". {resolution}",
+            hash=self.hash,
+            field=self.field,
+            base=self.base,
+            list=self.list,
+            sheet=self.sheet);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requirements::ListItem;
+
+    fn map(reqs: Vec<Requirement>) -> HashMap<String, Requirement> {
+        return reqs.into_iter().map(|x| (x.hash.clone(), x)).collect();
+    }
+
+    #[test]
+    fn merge_without_snapshot() {
+        let list = vec![Requirement::test("h1", 1).with_contents("List.")];
+        let sheet = map(vec![Requirement::test("h1", 0).with_contents("Sheet.")]);
+        let (output, conflicts) = merge_requirements(list, &sheet, None, &MergeRules::default(), false);
+        assert_eq!(output[0].contents, "List.");
+        assert_eq!(output[0].status, 0);
+        assert!(conflicts.is_empty());
+    }
+    #[test]
+    fn merge_one_sided_edits() {
+        let base = map(vec![Requirement::test("h1", 0).with_contents("A."), Requirement::test("h2", 0).with_contents("B.")]);
+        // Status ticked in list, contents edited in sheet.
+        let list = vec![Requirement::test("h1", 1).with_contents("A.").with_list_item(ListItem::Ordered(1)), Requirement::test("h2", 0).with_contents("B.")];
+        let sheet = map(vec![Requirement::test("h1", 0).with_contents("A!"), Requirement::test("h2", 5).with_contents("B.")]);
+        let (output, conflicts) = merge_requirements(list, &sheet, Some(&base), &MergeRules::default(), false);
+
+        assert!(conflicts.is_empty());
+        assert_eq!(output[0].contents, "A!");
+        assert_eq!(output[0].status, 1);
+        assert_eq!(output[0].to_text_format(), "1. [x] A!(@h1)");
+        assert_eq!(output[1].status, 5);
+    }
+    #[test]
+    fn merge_conflicting_edits() {
+        let base = map(vec![Requirement::test("h1", 0).with_contents("A.")]);
+        let list = vec![Requirement::test("h1", 1).with_contents("List.")];
+        let sheet = map(vec![Requirement::test("h1", 2).with_contents("Sheet.")]);
+        let (output, conflicts) = merge_requirements(list, &sheet, Some(&base), &MergeRules::default(), false);
+
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].field, MergeField::Contents);
+        assert_eq!(conflicts[1].field, MergeField::Status);
+        assert_eq!(output[0].contents, "List.");
+        assert_eq!(output[0].status, 2);
+    }
+    #[test]
+    fn merge_deletions() {
+        let base = map(vec![Requirement::test("h1", 0).with_contents("A."), Requirement::test("h2", 0).with_contents("B."), Requirement::test("h3", 0).with_contents("C.")]);
+        // h1 deleted in sheet, h2 deleted in sheet but edited in list, h3 deleted in list,
+        // h4 added in sheet.
+        let list = vec![Requirement::test("h1", 0).with_contents("A."), Requirement::test("h2", 0).with_contents("B!")];
+        let sheet = map(vec![Requirement::test("h3", 0).with_contents("C."), Requirement::test("h4", 0).with_contents("D.")]);
+        let (output, conflicts) = merge_requirements(list, &sheet, Some(&base), &MergeRules::default(), false);
+
+        assert_eq!(output.len(), 1);
+        assert_eq!(output[0].hash, "h2");
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(conflicts[0].hash, "h2");
+        assert_eq!(conflicts[1].hash, "h4");
+    }
+    #[test]
+    fn merge_with_authority() {
+        let rules = MergeRules { status_from: Authority::List, contents_from: Authority::Sheet, list_is_newer: false };
+        let list = vec![Requirement::test("h1", 1).with_contents("List.").with_list_item(ListItem::Ordered(1))];
+        let sheet = map(vec![Requirement::test("h1", 0).with_contents("Sheet.")]);
+        let (output, _) = merge_requirements(list, &sheet, None, &rules, false);
+        assert_eq!(output[0].contents, "Sheet.");
+        assert_eq!(output[0].status, 1);
+        assert_eq!(output[0].to_text_format(), "1. [x] Sheet.(@h1)");
+
+        // Conflicts are resolved by the newest file.
+        let rules = MergeRules { status_from: Authority::Newest, contents_from: Authority::Newest, list_is_newer: true };
+        let base = map(vec![Requirement::test("h1", 0).with_contents("A.")]);
+        let list = vec![Requirement::test("h1", 1).with_contents("List.")];
+        let sheet = map(vec![Requirement::test("h1", 2).with_contents("Sheet.")]);
+        let (output, conflicts) = merge_requirements(list, &sheet, Some(&base), &rules, false);
+        assert_eq!(output[0].contents, "List.");
+        assert_eq!(output[0].status, 1);
+        assert_eq!(conflicts[1].resolution, Authority::List);
+    }
+    #[test]
+    fn merge_unassigned_objective() {
+        // E.g. a toml list, which keeps the objective, after ody objective unassign cleared it in the sheet.
+        let assigned = Requirement::test("h1", 0).with_contents("A.").with_objective("1.0.0");
+        let base = map(vec![assigned.clone()]);
+        let list = vec![assigned, Requirement::test("h2", 0).with_contents("B.")];
+        let sheet = map(vec![Requirement::test("h1", 0).with_contents("A."), Requirement::test("h2", 0).with_contents("B.").with_objective("1.1.0")]);
+        let (output, conflicts) = merge_requirements(list, &sheet, Some(&base), &MergeRules::default(), false);
+        assert!(conflicts.is_empty());
+        assert_eq!(output[0].objective, None);
+        assert_eq!(output[1].objective.as_deref(), Some("1.1.0"));
+    }
+    #[test]
+    fn get_snapshot_path() {
+        assert_eq!(snapshot_path(&PathBuf::from("path/to/reqs.csv")), PathBuf::from("path/to/.reqs.csv.snapshot"));
+    }
+}
@@ -1,29 +1,177 @@
 mod requirement_builder;
 mod requirement;
 mod list_parser;
-
-use std::{collections::HashMap, fs::File, hash::DefaultHasher, io::Read, path::PathBuf, rc::Rc};
+mod merge;
+mod diff;
+mod workbook;
+mod document;
+mod toml_format;
+mod html;
+mod progress;
+mod query;
+mod edit;
+mod history;
+mod burndown;
+mod trace;
+mod test_results;
+mod settings;
+
+use std::{collections::{BTreeMap, HashMap}, fs::File, hash::DefaultHasher, io::Read, path::PathBuf, rc::Rc};
+use chrono::{DateTime, FixedOffset};
 use regex::Regex;
 use mythos_core::{printerror, printinfo};
-
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+pub use merge::{merge_requirements, snapshot_path};
+pub use diff::diff_requirements;
+pub use workbook::{is_workbook, parse_workbook, write_workbook};
+pub use document::{is_document, parse_document};
+pub use toml_format::{is_toml, parse_toml_requirements, to_toml_format};
+pub use query::Filter;
+pub use edit::{find_requirement, find_requirements, lookup_requirement, EditableFile, Transaction};
+pub use history::{apply_history, detect_status_changes, history_path, read_history, append_history, StatusChange, StatusSource};
+pub use burndown::{Burndown, BurndownGroup};
+pub use trace::{scan_function_tags, scan_sources, Trace};
+pub use test_results::{apply_test_results, read_test_results};
+pub use settings::{HashAlgorithm, ListSettings};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ListItem { Ordered(usize), Unordered, Todo(char), Hybrid(usize, char) }
 
 struct ListParser(Regex);
 
-struct RequirementBuilder(Regex, DefaultHasher, HashMap<String, String>);
+/// Hash regex, default hasher, categories, hash algorithm, and how often each fnv1a input was hashed.
+struct RequirementBuilder(Regex, DefaultHasher, HashMap<String, String>, HashAlgorithm, HashMap<String, usize>);
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Requirement {
     pub category: Rc<String>,
     pub hash: String,
+    /// Written as "x.x.x" in json/yaml.
+    #[serde(serialize_with = "document::serialize_id", deserialize_with = "document::deserialize_id")]
     pub id: Vec<usize>,
     pub contents: String,
+    #[serde(default = "document::default_list_item")]
     pub list_item: ListItem,
     pub status: u8,
+    /// Version of the project this requirement is part of.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub objective: Option<String>,
+    /// First status change in the spreadsheet's history. Derived on sync, see history::apply_history.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<FixedOffset>>,
+    /// When the requirement was last marked done, if it still is. Derived like created_at.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<DateTime<FixedOffset>>,
+    /// Where the requirement is referenced in source code, e.g. "src/main.rs:12; tests/cli.rs:40". Written by ody trace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace: Option<String>,
+}
+
+/// A requirement list, as it is written to json/yaml.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RequirementSet {
+    /// Category abbreviation -> category header from the list file.
+    #[serde(default)]
+    pub categories: BTreeMap<String, String>,
+    pub requirements: Vec<Requirement>,
+}
+
+/// Field of a requirement that can be edited in both the list and the spreadsheet.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MergeField { Contents, Status, Existence }
+
+/// Which file's value is kept when a field differs between the list and the spreadsheet.
+/// Newest picks whichever file was modified last.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Authority { List, Sheet, Newest }
+
+#[derive(Debug, Clone)]
+pub struct MergeRules {
+    pub status_from: Authority,
+    pub contents_from: Authority,
+    /// Used to resolve Authority::Newest.
+    pub list_is_newer: bool,
+}
+
+/// A field that was changed differently in the list and the spreadsheet since the last sync.
+#[derive(Debug, Clone)]
+pub struct MergeConflict {
+    pub hash: String,
+    pub field: MergeField,
+    pub base: String,
+    pub list: String,
+    pub sheet: String,
+    /// Side whose value was kept. Either List or Sheet.
+    pub resolution: Authority,
+}
+
+/// A single way in which a requirement differs between two versions of a spreadsheet.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Change {
+    Added,
+    Removed,
+    /// Id changed.
+    Moved { from: String, to: String },
+    /// Contents changed.
+    Renamed { from: String, to: String },
+    #[serde(rename = "status")]
+    StatusChanged { from: u8, to: u8 },
+    Recategorised { from: String, to: String },
+}
+
+/// All changes made to one requirement. Identifying info is taken from the newer version.
+#[derive(Debug, Clone, Serialize)]
+pub struct RequirementDiff {
+    pub hash: String,
+    pub category: String,
+    pub id: String,
+    pub contents: String,
+    pub changes: Vec<Change>,
+}
+
+/// Number of requirements in a group, and how many of them are done.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct Progress {
+    pub done: usize,
+    pub total: usize,
+}
+
+/// Progress of a category, top level requirement or objective.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressGroup {
+    /// Category abbreviation, requirement id or objective.
+    pub key: String,
+    /// Category long name or requirement contents. Same as key for objectives.
+    pub name: String,
+    #[serde(flatten)]
+    pub progress: Progress,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryProgress {
+    #[serde(flatten)]
+    pub group: ProgressGroup,
+    /// Each top level requirement, counting all of its children.
+    pub requirements: Vec<ProgressGroup>,
+}
+
+/// Summary of a requirement set, shared by `ody stats`, the html report and project dashboards.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ProgressReport {
+    pub total: Progress,
+    /// Number of requirements with each status label.
+    pub by_status: BTreeMap<String, usize>,
+    pub categories: Vec<CategoryProgress>,
+    pub objectives: Vec<ProgressGroup>,
 }
 
 pub fn parse_requirements(path: &PathBuf, be_verbose: bool) -> Option<(Vec<Requirement>, HashMap<String, String>)> {
+    if is_toml(path) {
+        return parse_toml_requirements(path, be_verbose);
+    }
     let contents = match File::open(path) {
         Ok(mut file) => {
             let mut output = String::new();
@@ -38,6 +186,21 @@ pub fn parse_requirements(path: &PathBuf, be_verbose: bool) -> Option<(Vec<Requi
             return None;
         },
     };
+    printinfo!(be_verbose, "Reading {path:?}");
+    return parse_requirements_str(&contents, be_verbose);
+}
+
+/// Same as parse_requirements, but reads the list from any source (e.g. stdin).
+pub fn parse_requirements_reader<R: Read>(mut reader: R, be_verbose: bool) -> Option<(Vec<Requirement>, HashMap<String, String>)> {
+    let mut contents = String::new();
+    if let Err(err) = reader.read_to_string(&mut contents) {
+        printerror!("Could not read requirements. {err}");
+        return None;
+    }
+    return parse_requirements_str(&contents, be_verbose);
+}
+
+pub fn parse_requirements_str(contents: &str, be_verbose: bool) -> Option<(Vec<Requirement>, HashMap<String, String>)> {
     let cat_regex =  Regex::new(r"\(.*\)$").unwrap();
     let mut builder = RequirementBuilder::new();
     let parser = ListParser::new();
@@ -47,16 +210,12 @@ pub fn parse_requirements(path: &PathBuf, be_verbose: bool) -> Option<(Vec<Requi
     let mut category = Rc::new(String::new());
     let mut prev_tab_level = 0;
 
-
-    printinfo!(be_verbose, "Reading {path:?}");
-
     for (i, line) in contents.split("\n").enumerate() {
         // Case 1: Skip.
         if line.is_empty() { continue; }
         printinfo!(be_verbose, "Line#{i}: \"{line}\"");
 
-        // let tab_level = line.replace("    ", "\t").matches("\t").count();
-        let tab_level = count_starting_tabs(&line);
+        let tab_level = ListSettings::get().indent_level(line);
         printinfo!(be_verbose, "Tab level: {0}", line.matches(" ").count());
 
         let mut content = line.trim().to_string();
@@ -89,6 +248,63 @@ pub fn parse_requirements(path: &PathBuf, be_verbose: bool) -> Option<(Vec<Requi
     return Some((output, builder.2));
 }
 
+/**
+ * Read requirements from either a list file or a spreadsheet, for commands that only need to
+ * look at them. Native spreadsheets, json/yaml and toml are detected by extension. Other files
+ * are treated as spreadsheets if they begin with a csv/md header, otherwise as list files.
+ */
+pub fn parse_requirement_set(path: &PathBuf, be_verbose: bool) -> Option<RequirementSet> {
+    if is_workbook(path) {
+        return Some(RequirementSet::from_spreadsheet(parse_workbook(path, be_verbose)?));
+    }
+
+    let contents = if path.as_os_str() == "-" {
+        let mut contents = String::new();
+        if let Err(err) = std::io::stdin().read_to_string(&mut contents) {
+            printerror!("Could not read requirements. {err}");
+            return None;
+        }
+        contents
+    } else {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) => {
+                printerror!("Could not open requirements file. {err}");
+                return None;
+            }
+        }
+    };
+    printinfo!(be_verbose, "Reading {path:?}");
+    return parse_requirement_set_str(path, &contents, be_verbose);
+}
+
+/// Same as parse_requirement_set, for the contents of $path read some other way (e.g. from git).
+/// Workbooks can't be read from text.
+pub fn parse_requirement_set_str(path: &PathBuf, contents: &str, be_verbose: bool) -> Option<RequirementSet> {
+    if is_toml(path) {
+        let (reqs, categories) = toml_format::parse_toml_requirements_str(contents, be_verbose)?;
+        return Some(RequirementSet::new(&reqs, &categories));
+    }
+    if is_document(path) {
+        return if path.extension().is_some_and(|x| x == "json") {
+            RequirementSet::from_json(contents)
+        } else {
+            RequirementSet::from_yaml(contents)
+        };
+    }
+    if is_workbook(path) {
+        printerror!("Workbooks like {path:?} can't be read as text.");
+        return None;
+    }
+
+    let header = contents.lines().find(|x| !x.trim().is_empty()).unwrap_or("");
+    if header.trim().starts_with("Hash,") || Requirement::check_md_header(header) {
+        return Some(RequirementSet::from_spreadsheet(parse_spreadsheet_str(contents, be_verbose)?));
+    }
+    let (reqs, categories) = parse_requirements_str(contents, be_verbose)?;
+    return Some(RequirementSet::new(&reqs, &categories));
+}
+
 fn parse_category(regex: &Regex, content: &String) -> Rc<String> {
     let category = match regex.find(&content) {
         // Unwrap is safe here b/c "()" is part of the regex definition.
@@ -127,19 +343,17 @@ fn calculate_id(id: &mut Vec<usize>, prev_tab_level: usize, curr_tab_level: usiz
     }
 }
 
-/// Count only the beginning whitespace. Tabs and spaces are treated as equal.
-fn count_starting_tabs(line: &str) -> usize {
-    let mut counter = 0;
-    for ch in line.chars() {
-        if !ch.is_whitespace() {
-            break;
-        }
-        counter += 1;
-    }
-    return counter;
-}
-
 pub fn parse_spreadsheet(path: &PathBuf, be_verbose: bool) -> Option<HashMap<String, Requirement>> {
+    if is_workbook(path) {
+        return parse_workbook(path, be_verbose);
+    }
+    if is_document(path) {
+        return parse_document(path, be_verbose);
+    }
+    if is_toml(path) {
+        let reqs = parse_toml_requirements(path, be_verbose)?.0;
+        return Some(reqs.into_iter().map(|x| (x.hash.clone(), x)).collect());
+    }
     let contents = match File::open(path) {
         Ok(mut file) => {
             let mut output = String::new();
@@ -154,14 +368,27 @@ pub fn parse_spreadsheet(path: &PathBuf, be_verbose: bool) -> Option<HashMap<Str
             return None;
         },
     };
-    let mut output: HashMap<String, Requirement> = HashMap::new();
-
     printinfo!(be_verbose, "\nReading {path:?}");
+    return parse_spreadsheet_str(&contents, be_verbose);
+}
+
+/// Same as parse_spreadsheet, but reads the table from any source (e.g. stdin).
+pub fn parse_spreadsheet_reader<R: Read>(mut reader: R, be_verbose: bool) -> Option<HashMap<String, Requirement>> {
+    let mut contents = String::new();
+    if let Err(err) = reader.read_to_string(&mut contents) {
+        printerror!("Error reading spreadsheet. \"{err}\".");
+        return None;
+    }
+    return parse_spreadsheet_str(&contents, be_verbose);
+}
+
+pub fn parse_spreadsheet_str(contents: &str, be_verbose: bool) -> Option<HashMap<String, Requirement>> {
+    let mut output: HashMap<String, Requirement> = HashMap::new();
 
     // Detect whether this is a csv file or md.
     let mut use_md_format: bool = false;
 
-    // Hash,Category,Id,Name,Status
+    // Hash,Category,Id,Contents,Status,Objective,Created,Completed,Trace (see workbook::HEADER)
     for (i, line) in contents.split("\n").enumerate() {
         if line.is_empty() { continue; }
         if i == 0 {
@@ -186,47 +413,61 @@ pub fn parse_spreadsheet(path: &PathBuf, be_verbose: bool) -> Option<HashMap<Str
             line.split(",").map(|x| x.trim()).collect()
         };
 
-        let count = values.len();
-        if  count != 5 {
-            printerror!("Error parsing input spreadsheet on line {i}. There should be 5 items, but found {count}. Line contents: \"{line}\"");
-            return None;
-        } 
-        let (hash, category, id, content, status) = (values[0], values[1], values[2], values[3], values[4]);
-
-        let status = match parse_csv_status(status) {
-            Ok(val) => val,
-            Err(_) => {
-                printerror!("Error on line {i}. Couldn't parse status. Status = \"{status}\".");
-                return None;
-            }
-        };
-        let id: Vec<usize> = id.split(".").map(|x| x.parse::<usize>().unwrap_or(0)).collect(); 
+        parse_spreadsheet_row(&values, i, &mut output)?;
+    }
 
-        let ch = RequirementBuilder::map_status_to_char(status);
-        let list_item = if ch != ' ' {
-            ListItem::Hybrid(*id.last().unwrap_or(&0), ch)
-        } else {
-            ListItem::Ordered(*id.last().unwrap_or(&0))
-        };
+    return Some(output);
+}
 
-        let req = Requirement {
-            category: Rc::new(category.to_string()),
-            list_item,
-            id, 
-            hash: hash.to_string(),
-            contents: content.to_string(),
-            status,
-        };
-        if let Some(collision) = output.insert(hash.to_string().clone(), req.clone()) {
-            printerror!("There was a hash collision while reading the requirements file.");
-            printerror!("Original value: {collision:?}");
-            printerror!("New value (@line {i}: {hash:?}");
-            printerror!("Colliding hash: {req:?}");
+/// Parse a row of values and add it to output. Shared by all spreadsheet formats.
+fn parse_spreadsheet_row(values: &[&str], i: usize, output: &mut HashMap<String, Requirement>) -> Option<()> {
+    let count = values.len();
+    if !(5..=9).contains(&count) {
+        printerror!("Error parsing input spreadsheet on line {i}. There should be 5 to 9 items, but found {count}. Line contents: \"{}\"", values.join(","));
+        return None;
+    } 
+    let (hash, category, id, content, status) = (values[0], values[1], values[2], values[3], values[4]);
+    // Objective, timestamp and trace columns are optional.
+    let objective = values.get(5).filter(|x| !x.is_empty()).map(|x| x.to_string());
+    let timestamp = |i: usize| values.get(i).and_then(|x| DateTime::parse_from_rfc3339(x).ok());
+
+    let status = match parse_csv_status(status) {
+        Ok(val) => val,
+        Err(_) => {
+            printerror!("Error on line {i}. Couldn't parse status. Status = \"{status}\".");
+            return None;
         }
-    }
+    };
+    let id: Vec<usize> = id.split(".").map(|x| x.parse::<usize>().unwrap_or(0)).collect(); 
 
-    return Some(output);
+    let ch = RequirementBuilder::map_status_to_char(status);
+    let list_item = if ch != ' ' {
+        ListItem::Hybrid(*id.last().unwrap_or(&0), ch)
+    } else {
+        ListItem::Ordered(*id.last().unwrap_or(&0))
+    };
+
+    let req = Requirement {
+        category: Rc::new(category.to_string()),
+        list_item,
+        id, 
+        hash: hash.to_string(),
+        contents: content.to_string(),
+        status,
+        objective,
+        created_at: timestamp(6),
+        completed_at: timestamp(7),
+        trace: values.get(8).filter(|x| !x.is_empty()).map(|x| x.to_string()),
+    };
+    if let Some(collision) = output.insert(hash.to_string().clone(), req.clone()) {
+        printerror!("There was a hash collision while reading the requirements file.");
+        printerror!("Original value: {collision:?}");
+        printerror!("New value (@line {i}: {hash:?}");
+        printerror!("Colliding hash: {req:?}");
+    }
+    return Some(());
 }
+
 fn parse_csv_status(status: &str) -> Result<u8, ()> {
     match status.parse::<u8>() {
         Ok(status) => return Ok(status),
@@ -297,6 +538,26 @@ mod tests {
         assert_eq!(req.status, 0);
     }
     #[test]
+    fn try_parse_from_str() {
+        let (reqs, categories) = parse_requirements_str("Category (CAT)\n1. Item.(@h1)\n\t- [x] Sub item.(@h2)\n", true).unwrap();
+        assert_eq!(reqs.len(), 2);
+        assert_eq!(reqs[1].id_to_string(), "1.1".to_string());
+        assert_eq!(reqs[1].status, 1);
+        assert_eq!(categories["CAT"], "Category (CAT)".to_string());
+
+        let csv = parse_spreadsheet_str("Hash,Category,Id,Contents,Status\nh1,CAT,1,Item.,0\n", true).unwrap();
+        assert_eq!(csv["h1"].contents, "Item.".to_string());
+    }
+    #[test]
+    fn try_parse_from_reader() {
+        let file = File::open("tests/test.md").unwrap();
+        let reqs = parse_spreadsheet_reader(file, true).unwrap();
+        assert_eq!(reqs.len(), 3);
+
+        let reqs = parse_requirements_reader("Cat\n1. ASDF(@h1)\n".as_bytes(), true).unwrap().0;
+        assert_eq!(reqs[0].hash, "h1".to_string());
+    }
+    #[test]
     fn test_todo_items() {
         let reqs = parse_requirements(&PathBuf::from("tests/test_todo.txt"), true).unwrap().0;
         let csv = parse_spreadsheet(&PathBuf::from("tests/test_todo.csv"), true).unwrap();
@@ -327,6 +588,10 @@ mod tests {
             contents: "contents.".to_string(),
             list_item: ListItem::Ordered(1),
             status: 0,
+            objective: None,
+            created_at: None,
+            completed_at: None,
+            trace: None,
         };
         assert_eq!(req.to_text_format(), "\t\t1. contents.(@hash)");
     }
@@ -339,9 +604,13 @@ mod tests {
             contents: "contents.".to_string(),
             list_item: ListItem::Ordered(1),
             status: 0,
+            objective: None,
+            created_at: None,
+            completed_at: None,
+            trace: None,
         };
-        // Hash,Category,Id,Name,Status
-        assert_eq!(req.to_csv_format(), "hash,CAT,1.1.1,contents.,0\n");
+        // Hash,Category,Id,Contents,Status,Objective,Created,Completed,Trace
+        assert_eq!(req.to_csv_format(), "hash,CAT,1.1.1,contents.,0,,,,\n");
     }
     #[test]
     fn print_to_md() {
@@ -352,9 +621,13 @@ mod tests {
             contents: "contents.".to_string(),
             list_item: ListItem::Ordered(1),
             status: 0,
+            objective: None,
+            created_at: None,
+            completed_at: None,
+            trace: None,
         };
-        // Hash,Category,Id,Name,Status
-        assert_eq!(req.to_md_format(), "|hash|CAT|1.1.1|contents.|0|\n");
+        // Hash,Category,Id,Contents,Status,Objective,Created,Completed,Trace
+        assert_eq!(req.to_md_format(), "|hash|CAT|1.1.1|contents.|0|||||\n");
     }
 }
 
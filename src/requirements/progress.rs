@@ -0,0 +1,161 @@
+use mythos_core::printerror;
+
+use super::{CategoryProgress, Progress, ProgressGroup, ProgressReport, Requirement, RequirementSet};
+
+impl Progress {
+    fn add(&mut self, req: &Requirement) {
+        self.total += 1;
+        if req.is_complete() {
+            self.done += 1;
+        }
+    }
+
+    pub fn percent(&self) -> f32 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        return self.done as f32 * 100.0 / self.total as f32;
+    }
+}
+
+impl ProgressGroup {
+    fn new(key: &str, name: &str) -> ProgressGroup {
+        return ProgressGroup { key: key.to_string(), name: name.to_string(), progress: Progress::default() };
+    }
+}
+
+impl ProgressReport {
+    /**
+     * Categories, top level requirements and objectives are listed in the order they first appear.
+     * Requirements whose parent is missing are counted under the top level id they would belong to.
+     */
+    pub fn new(set: &RequirementSet) -> ProgressReport {
+        let mut report = ProgressReport {
+            total: Progress::default(),
+            by_status: Default::default(),
+            categories: Vec::new(),
+            objectives: Vec::new(),
+        };
+
+        for req in &set.requirements {
+            report.total.add(req);
+            *report.by_status.entry(req.status_label()).or_insert(0) += 1;
+
+            let category = match report.categories.iter().position(|x| x.group.key == *req.category) {
+                Some(i) => &mut report.categories[i],
+                None => {
+                    let name = set.categories.get(req.category.as_str()).unwrap_or(&req.category);
+                    report.categories.push(CategoryProgress {
+                        group: ProgressGroup::new(&req.category, name),
+                        requirements: Vec::new(),
+                    });
+                    report.categories.last_mut().unwrap()
+                }
+            };
+            category.group.progress.add(req);
+
+            let top_level = req.id.first().map(|x| x.to_string()).unwrap_or_default();
+            let parent = match category.requirements.iter().position(|x| x.key == top_level) {
+                Some(i) => &mut category.requirements[i],
+                None => {
+                    let name = if req.id.len() == 1 { req.contents.as_str() } else { "" };
+                    category.requirements.push(ProgressGroup::new(&top_level, name));
+                    category.requirements.last_mut().unwrap()
+                }
+            };
+            if req.id.len() == 1 {
+                parent.name = req.contents.clone();
+            }
+            parent.progress.add(req);
+
+            if let Some(objective) = &req.objective {
+                let group = match report.objectives.iter().position(|x| x.key == *objective) {
+                    Some(i) => &mut report.objectives[i],
+                    None => {
+                        report.objectives.push(ProgressGroup::new(objective, objective));
+                        report.objectives.last_mut().unwrap()
+                    }
+                };
+                group.progress.add(req);
+            }
+        }
+        return report;
+    }
+
+    pub fn to_text_format(&self) -> String {
+        let line = |name: &str, progress: &Progress| format!("{name}: {}/{} done ({:.0}%)\n", progress.done, progress.total, progress.percent());
+
+        let mut output = line("Total", &self.total);
+        output += &format!("Status: {}\n",
+            self.by_status.iter().map(|(status, count)| format!("{status} {count}")).collect::<Vec<String>>().join(", "));
+
+        for category in &self.categories {
+            output += "\n";
+            output += &line(&format!("{} ({})", category.group.name, category.group.key), &category.group.progress);
+            for req in &category.requirements {
+                output += &format!("    {}", line(&format!("{}. {}", req.key, req.name), &req.progress));
+            }
+        }
+
+        if !self.objectives.is_empty() {
+            output += "\nObjectives:\n";
+            for objective in &self.objectives {
+                output += &format!("    {}", line(&objective.key, &objective.progress));
+            }
+        }
+        return output;
+    }
+
+    pub fn to_json_format(&self) -> Option<String> {
+        return match serde_json::to_string_pretty(self) {
+            Ok(output) => Some(output + "\n"),
+            Err(err) => {
+                printerror!("Could not convert progress report to json. {err}");
+                None
+            }
+        };
+    }
+
+    pub fn to_yaml_format(&self) -> Option<String> {
+        return match serde_yaml::to_string(self) {
+            Ok(output) => Some(output),
+            Err(err) => {
+                printerror!("Could not convert progress report to yaml. {err}");
+                None
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, path::PathBuf};
+
+    use super::*;
+    use crate::requirements::parse_requirements;
+
+    #[test]
+    fn progress_per_group() {
+        let (mut reqs, categories) = parse_requirements(&PathBuf::from("tests/test_compare.txt"), false).unwrap();
+        reqs[1].objective = Some("0.1.0".to_string());
+        reqs[2].objective = Some("0.1.0".to_string());
+        let report = ProgressReport::new(&RequirementSet::new(&reqs, &categories));
+
+        assert_eq!(report.total, Progress { done: 1, total: 3 });
+        assert_eq!(report.by_status, [("done".to_string(), 1), ("open".to_string(), 2)].into_iter().collect());
+        assert_eq!(report.categories.len(), 1);
+        assert_eq!(report.categories[0].requirements, vec![ProgressGroup {
+            key: "1".to_string(),
+            name: "ASDF".to_string(),
+            progress: Progress { done: 1, total: 3 },
+        }]);
+        assert_eq!(report.objectives[0].key, "0.1.0");
+        assert_eq!(report.objectives[0].progress, Progress { done: 1, total: 2 });
+    }
+    #[test]
+    fn empty_progress() {
+        let report = ProgressReport::new(&RequirementSet::new(&[], &HashMap::new()));
+        assert_eq!(report.total.percent(), 0.0);
+        assert_eq!(report.to_text_format(), "Total: 0/0 done (0%)\nStatus: \n");
+    }
+}
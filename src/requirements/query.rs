@@ -0,0 +1,274 @@
+use mythos_core::printerror;
+
+use super::{Requirement, RequirementBuilder, RequirementSet};
+
+/**
+ * Filter expression, e.g. `category=PROJ and id^=1 and status!=done and contents~"terminal"`.
+ *
+ * Fields: category, id, hash, contents, status, objective.
+ * Operators: `=` equals, `!=` not equals, `^=` starts with, `~` contains (case insensitive),
+ * `!~` does not contain.
+ * Comparisons can be combined with `and`, `or`, `not` and parentheses. Values containing
+ * spaces or operator characters must be quoted.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    Compare(Field, Op, String),
+    Not(Box<Filter>),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Field { Category, Id, Hash, Contents, Status, Objective }
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op { Equals, NotEquals, StartsWith, Contains, NotContains }
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token { Word(String), Quoted(String), Op(Op), Open, Close }
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        return match self {
+            Token::Word(word) => write!(f, "'{word}'"),
+            Token::Quoted(value) => write!(f, "{value:?}"),
+            Token::Op(op) => write!(f, "'{}'", match op {
+                Op::Equals => "=",
+                Op::NotEquals => "!=",
+                Op::StartsWith => "^=",
+                Op::Contains => "~",
+                Op::NotContains => "!~",
+            }),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        };
+    }
+}
+
+impl Filter {
+    pub fn parse(expr: &str) -> Option<Filter> {
+        let tokens = match tokenize(expr) {
+            Ok(tokens) => tokens,
+            Err(err) => {
+                printerror!("Invalid filter {expr:?}. {err}");
+                return None;
+            }
+        };
+        let mut parser = Parser { tokens, pos: 0 };
+        let filter = parser.parse_or().and_then(|filter| match parser.tokens.get(parser.pos) {
+            Some(token) => Err(format!("Unexpected {token}.")),
+            None => Ok(filter),
+        });
+        return match filter {
+            Ok(filter) => Some(filter),
+            Err(err) => {
+                printerror!("Invalid filter {expr:?}. {err}");
+                None
+            }
+        };
+    }
+
+    pub fn matches(&self, req: &Requirement) -> bool {
+        return match self {
+            Filter::Compare(field, op, value) => compare(req, *field, *op, value),
+            Filter::Not(filter) => !filter.matches(req),
+            Filter::And(a, b) => a.matches(req) && b.matches(req),
+            Filter::Or(a, b) => a.matches(req) || b.matches(req),
+        };
+    }
+
+}
+
+impl RequirementSet {
+    /// Drop requirements not matched by filter. No filter keeps everything.
+    pub fn retain_matching(&mut self, filter: Option<&Filter>) {
+        if let Some(filter) = filter {
+            self.requirements.retain(|x| filter.matches(x));
+        }
+    }
+}
+
+fn compare(req: &Requirement, field: Field, op: Op, value: &str) -> bool {
+    // Status can be matched by label (open/done), number or list mark.
+    if field == Field::Status && matches!(op, Op::Equals | Op::NotEquals) {
+        let mark = RequirementBuilder::map_status_to_char(req.status).to_string();
+        let is_equal = value.eq_ignore_ascii_case(&req.status_label())
+            || value == req.status.to_string()
+            || value == mark
+            || value == format!("[{mark}]");
+        return is_equal == (op == Op::Equals);
+    }
+    let actual = match field {
+        Field::Category => req.category.to_string(),
+        Field::Id => req.id_to_string(),
+        Field::Hash => req.hash.clone(),
+        Field::Contents => req.contents.clone(),
+        Field::Status => req.status_label(),
+        Field::Objective => req.objective.clone().unwrap_or_default(),
+    };
+    return match op {
+        Op::Equals => actual == value,
+        Op::NotEquals => actual != value,
+        // id^=1 matches 1 and 1.2, but not 10.
+        Op::StartsWith if field == Field::Id || field == Field::Objective => {
+            actual == value || actual.starts_with(&format!("{}.", value.trim_end_matches('.')))
+        },
+        Op::StartsWith => actual.starts_with(value),
+        Op::Contains => actual.to_lowercase().contains(&value.to_lowercase()),
+        Op::NotContains => !actual.to_lowercase().contains(&value.to_lowercase()),
+    };
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = expr.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            _ if ch.is_whitespace() => (),
+            '(' => tokens.push(Token::Open),
+            ')' => tokens.push(Token::Close),
+            '=' => tokens.push(Token::Op(Op::Equals)),
+            '~' => tokens.push(Token::Op(Op::Contains)),
+            '!' | '^' => {
+                tokens.push(Token::Op(match (ch, chars.next()) {
+                    ('!', Some('=')) => Op::NotEquals,
+                    ('!', Some('~')) => Op::NotContains,
+                    ('^', Some('=')) => Op::StartsWith,
+                    _ => return Err(format!("Unknown operator after '{ch}'.")),
+                }));
+            },
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => value.extend(chars.next()),
+                        Some(x) if x == ch => break,
+                        Some(x) => value.push(x),
+                        None => return Err("Unterminated string.".to_string()),
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            },
+            _ => {
+                let mut word = ch.to_string();
+                while let Some(&x) = chars.peek() {
+                    if x.is_whitespace() || "()=~!^\"'".contains(x) {
+                        break;
+                    }
+                    word.push(x);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    return Ok(tokens);
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        return token;
+    }
+    fn next_is_keyword(&self, keyword: &str) -> bool {
+        return matches!(self.tokens.get(self.pos), Some(Token::Word(x)) if x.eq_ignore_ascii_case(keyword));
+    }
+    fn parse_or(&mut self) -> Result<Filter, String> {
+        let mut filter = self.parse_and()?;
+        while self.next_is_keyword("or") {
+            self.pos += 1;
+            filter = Filter::Or(Box::new(filter), Box::new(self.parse_and()?));
+        }
+        return Ok(filter);
+    }
+    fn parse_and(&mut self) -> Result<Filter, String> {
+        let mut filter = self.parse_unary()?;
+        while self.next_is_keyword("and") {
+            self.pos += 1;
+            filter = Filter::And(Box::new(filter), Box::new(self.parse_unary()?));
+        }
+        return Ok(filter);
+    }
+    fn parse_unary(&mut self) -> Result<Filter, String> {
+        if self.next_is_keyword("not") {
+            self.pos += 1;
+            return Ok(Filter::Not(Box::new(self.parse_unary()?)));
+        }
+        let field = match self.next() {
+            Some(Token::Open) => {
+                let filter = self.parse_or()?;
+                return match self.next() {
+                    Some(Token::Close) => Ok(filter),
+                    _ => Err("Missing ')'.".to_string()),
+                };
+            },
+            Some(Token::Word(word)) => match word.to_lowercase().as_str() {
+                "category" | "cat" => Field::Category,
+                "id" => Field::Id,
+                "hash" => Field::Hash,
+                "contents" | "name" => Field::Contents,
+                "status" => Field::Status,
+                "objective" => Field::Objective,
+                _ => return Err(format!("Unknown field {word:?}. Expected one of: category, id, hash, contents, status, objective.")),
+            },
+            Some(token) => return Err(format!("Expected a field, found {token}.")),
+            None => return Err("Expected a field.".to_string()),
+        };
+        let op = match self.next() {
+            Some(Token::Op(op)) => op,
+            _ => return Err("Expected an operator after field.".to_string()),
+        };
+        return match self.next() {
+            Some(Token::Word(value)) | Some(Token::Quoted(value)) => Ok(Filter::Compare(field, op, value)),
+            Some(token) => Err(format!("Expected a value, found {token}.")),
+            None => Err("Expected a value.".to_string()),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_filter() {
+        let filter = Filter::parse("category=PROJ and not (id^=1 or contents~\"a b\")").unwrap();
+        assert_eq!(filter, Filter::And(
+            Box::new(Filter::Compare(Field::Category, Op::Equals, "PROJ".to_string())),
+            Box::new(Filter::Not(Box::new(Filter::Or(
+                Box::new(Filter::Compare(Field::Id, Op::StartsWith, "1".to_string())),
+                Box::new(Filter::Compare(Field::Contents, Op::Contains, "a b".to_string())),
+            )))),
+        ));
+        assert!(Filter::parse("category=").is_none());
+        assert!(Filter::parse("colour=red").is_none());
+        assert!(Filter::parse("(id=1").is_none());
+        assert!(Filter::parse("id=1 id=2").is_none());
+    }
+    #[test]
+    fn match_filter() {
+        let reqs = vec![
+            Requirement::test("PROJ[1]", 0).in_category("PROJ").with_id(&[1]).with_contents("Open a terminal").with_objective("0.2.0"),
+            Requirement::test("PROJ[1, 2]", 1).in_category("PROJ").with_id(&[1, 2]).with_contents("Close the Terminal").with_objective("0.2.0"),
+            Requirement::test("PROJ[10]", 0).in_category("PROJ").with_id(&[10]).with_contents("Open a terminal").with_objective("0.2.0"),
+            Requirement::test("PROJ[1, 3]", 0).in_category("PROJ").with_id(&[1, 3]).with_contents("Open a browser").with_objective("0.2.0"),
+            Requirement::test("DOC[1]", 0).in_category("DOC").with_id(&[1]).with_contents("Open a terminal"),
+        ];
+        let filter = Filter::parse("category=PROJ and id^=1 and status!=done and objective=0.2.0 and contents~\"terminal\"").unwrap();
+        let matched: Vec<&str> = reqs.iter().filter(|x| filter.matches(x)).map(|x| x.hash.as_str()).collect();
+        assert_eq!(matched, vec!["PROJ[1]"]);
+
+        let filter = Filter::parse("status=x or objective=\"\"").unwrap();
+        let mut set = RequirementSet::new(&reqs, &Default::default());
+        set.retain_matching(Some(&filter));
+        let matched: Vec<&str> = set.requirements.iter().map(|x| x.hash.as_str()).collect();
+        assert_eq!(matched, vec!["PROJ[1, 2]", "DOC[1]"]);
+    }
+}
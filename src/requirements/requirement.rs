@@ -1,40 +1,54 @@
 use mythos_core::printinfo;
 
-use super::{ListItem, Requirement, RequirementBuilder};
+use super::{history::format_timestamp, ListItem, ListSettings, Requirement, RequirementBuilder};
+
+/// Status of requirements whose tests fail, written as `[!]` in lists.
+pub const FAILED_STATUS: u8 = b'!';
 
 impl Requirement {
     pub fn to_text_format(&self) -> String {
-        let tabs = "\t".repeat(self.id.len() - 1);
-        let line_num = match self.list_item {
+        let indentation = ListSettings::get().indentation(self.id.len() - 1);
+        return format!("{indentation}{0} {1}(@{2})", self.list_item_header(), self.contents, self.hash);
+    }
+    /// List item prefix, e.g. "1.", "- [x]".
+    pub fn list_item_header(&self) -> String {
+        return match self.list_item {
             super::ListItem::Ordered(num) => format!("{num}."),
             super::ListItem::Unordered => format!("-"),
             super::ListItem::Todo(ch) => format!("- [{ch}]"),
             super::ListItem::Hybrid(num, ch) => format!("{num}. [{ch}]"),
         };
-        return format!("{tabs}{line_num} {0}(@{1})", self.contents, self.hash);
     }
     pub fn to_csv_format(&self) -> String {
-        // Hash,Category,Id,Name,Status
-        return format!("{hash},{cat},{id},{contents},{status}\n", 
+        // Hash,Category,Id,Contents,Status,Objective,Created,Completed,Trace
+        return format!("{hash},{cat},{id},{contents},{status},{objective},{created},{completed},{trace}\n", 
             hash=self.hash, 
             cat=self.category,
             id=self.id_to_string(),
             contents=self.contents,
-            status=self.status);
+            status=self.status,
+            objective=self.objective.as_deref().unwrap_or(""),
+            created=format_timestamp(self.created_at.as_ref()),
+            completed=format_timestamp(self.completed_at.as_ref()),
+            trace=self.trace.as_deref().unwrap_or(""));
     }
     pub fn to_md_format(&self) -> String {
-        return format!("|{hash}|{cat}|{id}|{contents}|{status}|\n", 
+        return format!("|{hash}|{cat}|{id}|{contents}|{status}|{objective}|{created}|{completed}|{trace}|\n", 
             hash=self.hash, 
             cat=self.category,
             id=self.id_to_string(),
             contents=self.contents,
-            status=self.status);
+            status=self.status,
+            objective=self.objective.as_deref().unwrap_or(""),
+            created=format_timestamp(self.created_at.as_ref()),
+            completed=format_timestamp(self.completed_at.as_ref()),
+            trace=self.trace.as_deref().unwrap_or(""));
     }
     pub const fn get_csv_header() -> &'static str {
-        return "Hash,Category,Id,Contents,Status\n";
+        return "Hash,Category,Id,Contents,Status,Objective,Created,Completed,Trace\n";
     }
     pub const fn get_md_header() -> &'static str {
-        return "|Hash|Category|Id|Contents|Status|\n|---|---|---|---|---|\n";
+        return "|Hash|Category|Id|Contents|Status|Objective|Created|Completed|Trace|\n|---|---|---|---|---|---|---|---|---|\n";
     }
 
     pub fn id_to_string(&self) -> String {
@@ -46,7 +60,11 @@ impl Requirement {
             printinfo!("Overwriting status with value from csv file: {} -> {}.", self.status, other.status); 
         }
 
-        self.status = other.status;
+        self.set_status(other.status);
+    }
+    /// Update status and the list item's mark so both stay in agreement.
+    pub fn set_status(&mut self, status: u8) {
+        self.status = status;
         match self.list_item {
             ListItem::Todo(_) => {
                 self.list_item = ListItem::Todo(RequirementBuilder::map_status_to_char(self.status));
@@ -70,6 +88,39 @@ impl Requirement {
             }
         }
     }
+    pub fn is_complete(&self) -> bool {
+        return self.status == 1;
+    }
+    /// Human readable status. Statuses other than open, done, failed and those labelled in odysseus.toml are shown as their list mark.
+    pub fn status_label(&self) -> String {
+        return Requirement::label_status(self.status);
+    }
+    pub fn label_status(status: u8) -> String {
+        if let Some(label) = ListSettings::get().label(status) {
+            return label.to_string();
+        }
+        return match status {
+            0 => "open".to_string(),
+            1 => "done".to_string(),
+            FAILED_STATUS => "failed".to_string(),
+            _ => format!("[{}]", RequirementBuilder::map_status_to_char(status)),
+        };
+    }
+    /// Read a status given as a label (open/done/failed), number or list mark (e.g. "-" or "[-]").
+    pub fn parse_status(value: &str) -> Option<u8> {
+        let value = value.trim_start_matches('[').trim_end_matches(']');
+        if let Some(status) = ListSettings::get().status(value) {
+            return Some(status);
+        }
+        return match value {
+            "open" | " " | "" => Some(0),
+            "done" | "x" => Some(1),
+            "failed" => Some(FAILED_STATUS),
+            _ if value.parse::<u8>().is_ok() => value.parse::<u8>().ok(),
+            _ if value.len() == 1 && value.is_ascii() => Some(value.as_bytes()[0]),
+            _ => None,
+        };
+    }
     pub fn check_md_header(line: &str) -> bool {
         // Remove all whitespace.
         let mut cleaned_line = String::new();
@@ -83,3 +134,48 @@ impl Requirement {
 }
 
 
+
+/**
+ * Requirements for tests. Every field is set here, so new fields only need adding once. E.g.
+ *     Requirement::test("h1", 1).with_contents("Parse lists.").with_objective("1.0.0")
+ */
+#[cfg(test)]
+impl Requirement {
+    /// An unordered CAT 1 requirement without contents.
+    pub fn test(hash: &str, status: u8) -> Requirement {
+        return Requirement {
+            category: std::rc::Rc::new("CAT".to_string()),
+            hash: hash.to_string(),
+            id: vec![1],
+            contents: String::new(),
+            list_item: ListItem::Unordered,
+            status,
+            objective: None,
+            created_at: None,
+            completed_at: None,
+            trace: None,
+        };
+    }
+    pub fn in_category(mut self, category: &str) -> Requirement {
+        self.category = std::rc::Rc::new(category.to_string());
+        return self;
+    }
+    pub fn with_id(mut self, id: &[usize]) -> Requirement {
+        self.id = id.to_vec();
+        return self;
+    }
+    pub fn with_contents(mut self, contents: &str) -> Requirement {
+        self.contents = contents.to_string();
+        return self;
+    }
+    /// Also sets the list item's mark from the status, like set_status.
+    pub fn with_list_item(mut self, list_item: ListItem) -> Requirement {
+        self.list_item = list_item;
+        self.set_status(self.status);
+        return self;
+    }
+    pub fn with_objective(mut self, objective: &str) -> Requirement {
+        self.objective = Some(objective.to_string());
+        return self;
+    }
+}
@@ -1,12 +1,19 @@
-use super::{ListItem, Requirement, RequirementBuilder};
+use super::{settings::fnv1a, HashAlgorithm, ListItem, ListSettings, Requirement, RequirementBuilder};
 
 use std::{collections::HashMap, hash::{DefaultHasher, Hash, Hasher}, rc::Rc};
 use regex::Regex;
 
 impl RequirementBuilder {
     pub fn new() -> RequirementBuilder {
+        return RequirementBuilder::with_hash_algorithm(ListSettings::get().hash_algorithm);
+    }
+    pub fn with_hash_algorithm(hash_algorithm: HashAlgorithm) -> RequirementBuilder {
         // (@<hash>)
-        return RequirementBuilder(Regex::new(r"\(@\S*\)$").unwrap(), DefaultHasher::new(), HashMap::new());
+        return RequirementBuilder(Regex::new(r"\(@\S*\)$").unwrap(), DefaultHasher::new(), HashMap::new(), hash_algorithm, HashMap::new());
+    }
+    /// Whether $contents end in a (@<hash>) annotation, which build takes as the hash.
+    pub fn has_hash(&self, contents: &str) -> bool {
+        return self.0.is_match(contents);
     }
     pub fn build(&mut self, contents: String, id: Vec<usize>, category: Rc<String>, list_item: ListItem) -> Requirement {
         let content;
@@ -21,8 +28,24 @@ impl RequirementBuilder {
                 output[2..end_index].to_string()
             },
             None => {
-                contents.hash(&mut self.1);
-                let hash = self.1.finish();
+                let hash = match self.3 {
+                    HashAlgorithm::Default => {
+                        contents.hash(&mut self.1);
+                        self.1.finish()
+                    },
+                    HashAlgorithm::Fnv1a => {
+                        // Contents alone would give every "Write tests" the same hash. Repeats of the same input, e.g.
+                        // ody add retrying after a collision, are numbered.
+                        let id = id.iter().map(|x| x.to_string()).collect::<Vec<String>>().join(".");
+                        let input = format!("{category}\n{id}\n{contents}");
+                        let repeats = self.4.entry(input.clone()).or_insert(0);
+                        *repeats += 1;
+                        match *repeats {
+                            1 => fnv1a(&input),
+                            n => fnv1a(&format!("{input}\n{}", n - 1)),
+                        }
+                    },
+                };
                 content = contents;
                 format!("{hash}")
             }
@@ -35,6 +58,10 @@ impl RequirementBuilder {
                 contents: content,
                 status: RequirementBuilder::map_char_to_status(&list_item),
                 list_item,
+                objective: None,
+                created_at: None,
+                completed_at: None,
+                trace: None,
             };
     }
     pub fn add_new_category(&mut self, key: Rc<String>, val: &String) {
@@ -63,3 +90,27 @@ impl RequirementBuilder {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_duplicate_contents() {
+        let category = Rc::new("PROJ".to_string());
+        let mut builder = RequirementBuilder::with_hash_algorithm(HashAlgorithm::Fnv1a);
+        let mut build = |id: Vec<usize>| builder.build("Write tests".to_string(), id, category.clone(), ListItem::Unordered).hash;
+        let first = build(vec![1, 1]);
+        let second = build(vec![2, 1]);
+        let repeat = build(vec![1, 1]);
+        assert_ne!(first, second);
+        assert_ne!(first, repeat);
+        assert_ne!(second, repeat);
+
+        // The same requirement gets the same hash in every run.
+        let mut builder = RequirementBuilder::with_hash_algorithm(HashAlgorithm::Fnv1a);
+        assert_eq!(builder.build("Write tests".to_string(), vec![1, 1], category.clone(), ListItem::Unordered).hash, first);
+        // Annotated hashes are kept.
+        assert_eq!(builder.build("Write tests (@h1)".to_string(), vec![1, 1], category, ListItem::Unordered).hash, "h1");
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::OnceLock;
+
+use super::requirement::FAILED_STATUS;
+
+static SETTINGS: OnceLock<ListSettings> = OnceLock::new();
+
+/// How hashes are generated for requirements that don't have one yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum HashAlgorithm {
+    /// Rust's DefaultHasher, fed every new requirement of the file in turn.
+    #[default]
+    Default,
+    /// 64 bit FNV-1a of the category, id and contents, so the same requirement always gets the same hash.
+    Fnv1a,
+}
+
+/// How list files are written and read, and what statuses are called. Set from odysseus.toml before any file is read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListSettings {
+    /// Spaces per level of indentation. 0 indents with tabs.
+    pub indent_width: usize,
+    pub hash_algorithm: HashAlgorithm,
+    /// Status labels, e.g. ("blocked", b'-'). They take precedence over open, done and failed.
+    pub statuses: Vec<(String, u8)>,
+}
+
+impl HashAlgorithm {
+    pub fn parse(value: &str) -> Option<HashAlgorithm> {
+        return match value {
+            "default" => Some(HashAlgorithm::Default),
+            "fnv1a" => Some(HashAlgorithm::Fnv1a),
+            _ => None,
+        };
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            HashAlgorithm::Default => "default",
+            HashAlgorithm::Fnv1a => "fnv1a",
+        };
+    }
+}
+
+impl ListSettings {
+    /// The settings in use. Defaults if they were never set.
+    pub fn get() -> &'static ListSettings {
+        return SETTINGS.get_or_init(ListSettings::default);
+    }
+
+    /// Only the first call has an effect.
+    pub fn set(settings: ListSettings) {
+        let _ = SETTINGS.set(settings);
+    }
+
+    /// Open, done and failed, unless they are relabelled.
+    pub fn default_statuses() -> Vec<(String, u8)> {
+        return vec![("open".to_string(), 0), ("done".to_string(), 1), ("failed".to_string(), FAILED_STATUS)];
+    }
+
+    pub fn indentation(&self, level: usize) -> String {
+        return match self.indent_width {
+            0 => "\t".repeat(level),
+            width => " ".repeat(width * level),
+        };
+    }
+
+    /**
+     * Indentation level of a line. With tabs, every leading whitespace character counts as a level, so any
+     * consistent indentation works. Otherwise tabs count as a level and spaces as a level per indent_width.
+     */
+    pub fn indent_level(&self, line: &str) -> usize {
+        let indentation = line.chars().take_while(|x| x.is_whitespace());
+        if self.indent_width == 0 {
+            return indentation.count();
+        }
+        let (tabs, spaces) = indentation.fold((0, 0), |(tabs, spaces), x| if x == '\t' { (tabs + 1, spaces) } else { (tabs, spaces + 1) });
+        return tabs + spaces / self.indent_width;
+    }
+
+    pub fn label(&self, status: u8) -> Option<&str> {
+        return self.statuses.iter().rev().find(|(_, x)| *x == status).map(|(label, _)| label.as_str());
+    }
+
+    pub fn status(&self, label: &str) -> Option<u8> {
+        return self.statuses.iter().rev().find(|(x, _)| x == label).map(|(_, status)| *status);
+    }
+}
+
+/// 64 bit FNV-1a.
+pub fn fnv1a(value: &str) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in value.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return hash;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indentation() {
+        let tabs = ListSettings::default();
+        assert_eq!(tabs.indentation(2), "\t\t");
+        assert_eq!(tabs.indent_level("  \t- item"), 3);
+
+        let spaces = ListSettings { indent_width: 4, ..Default::default() };
+        assert_eq!(spaces.indentation(2), " ".repeat(8));
+        assert_eq!(spaces.indent_level("        - item"), 2);
+        assert_eq!(spaces.indent_level("\t    - item"), 2);
+        assert_eq!(spaces.indent_level("  - item"), 0);
+    }
+    #[test]
+    fn status_labels() {
+        let settings = ListSettings { statuses: vec![("blocked".to_string(), b'-'), ("complete".to_string(), 1)], ..Default::default() };
+        assert_eq!(settings.label(b'-'), Some("blocked"));
+        assert_eq!(settings.label(1), Some("complete"));
+        assert_eq!(settings.label(0), None);
+        assert_eq!(settings.status("blocked"), Some(b'-'));
+        assert_eq!(settings.status("open"), None);
+        assert_eq!(fnv1a(""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a("a"), 0xaf63dc4c8601ec8c);
+    }
+}
@@ -0,0 +1,209 @@
+use std::{collections::HashMap, fs, path::Path, sync::LazyLock};
+use chrono::{DateTime, FixedOffset};
+use mythos_core::printerror;
+use regex::Regex;
+use serde_json::Value;
+
+use super::{lookup_requirement, requirement::FAILED_STATUS, trace::tag_targets, Requirement, StatusChange, StatusSource};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestOutcome { Passed, Failed, Ignored }
+
+/// One test of a JUnit XML or `cargo test` json result file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub name: String,
+    pub outcome: TestOutcome,
+}
+
+static TESTCASE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?s)<testcase\b([^>]*?)(?:/>|>(.*?)</testcase>)").unwrap());
+static ATTRIBUTE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"(\w+)\s*=\s*(?:"([^"]*)"|'([^']*)')"#).unwrap());
+static HASH_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)(?:^|[^a-z0-9])req_(\d+)").unwrap());
+
+/// Read a JUnit XML file, or the json lines printed by `cargo test -- -Z unstable-options --format json`.
+pub fn read_test_results(path: &Path) -> Option<Vec<TestResult>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            printerror!("Could not read test results {path:?}. {err}");
+            return None;
+        }
+    };
+    if path.extension().is_some_and(|x| x == "xml") || contents.trim_start().starts_with('<') {
+        return parse_junit(&contents);
+    }
+    return parse_cargo_json(&contents);
+}
+
+/// Every <testcase>. Those containing a <failure> or <error> failed, and those containing <skipped> were ignored.
+fn parse_junit(contents: &str) -> Option<Vec<TestResult>> {
+    if !contents.contains("<testsuite") {
+        printerror!("Could not parse test results. Expected a JUnit <testsuite>.");
+        return None;
+    }
+
+    let mut results: Vec<TestResult> = Vec::new();
+    for captures in TESTCASE_REGEX.captures_iter(contents) {
+        let name = ATTRIBUTE_REGEX.captures_iter(&captures[1])
+            .find(|x| &x[1] == "name")
+            .and_then(|x| x.get(2).or(x.get(3)))
+            .map(|x| unescape_xml(x.as_str()));
+        let Some(name) = name else {
+            printerror!("Could not parse test results. A <testcase> has no name.");
+            return None;
+        };
+        let body = captures.get(2).map(|x| x.as_str()).unwrap_or("");
+        let outcome = if body.contains("<failure") || body.contains("<error") {
+            TestOutcome::Failed
+        } else if body.contains("<skipped") {
+            TestOutcome::Ignored
+        } else {
+            TestOutcome::Passed
+        };
+        results.push(TestResult { name, outcome });
+    }
+    return Some(results);
+}
+
+fn unescape_xml(value: &str) -> String {
+    return value.replace("&lt;", "<").replace("&gt;", ">").replace("&quot;", "\"").replace("&apos;", "'").replace("&amp;", "&");
+}
+
+/// Test events that finished. Lines that aren't json objects, e.g. compiler output, are skipped.
+fn parse_cargo_json(contents: &str) -> Option<Vec<TestResult>> {
+    let mut results: Vec<TestResult> = Vec::new();
+    for (i, line) in contents.lines().enumerate().filter(|(_, x)| x.trim_start().starts_with('{')) {
+        let event: Value = match serde_json::from_str(line) {
+            Ok(event) => event,
+            Err(err) => {
+                printerror!("Could not parse test results, line {}. {err}", i + 1);
+                return None;
+            }
+        };
+        if event["type"] != "test" {
+            continue;
+        }
+        let outcome = match event["event"].as_str() {
+            Some("ok") => TestOutcome::Passed,
+            Some("failed" | "timeout") => TestOutcome::Failed,
+            Some("ignored") => TestOutcome::Ignored,
+            _ => continue,
+        };
+        let Some(name) = event["name"].as_str() else { continue };
+        results.push(TestResult { name: name.to_string(), outcome });
+    }
+    return Some(results);
+}
+
+/**
+ * "@hash" or id of the requirements a test is tagged with: REQ tags or `req_<hash>` in its name, and tags in
+ * the comments above its function in $function_tags (see scan_function_tags).
+ */
+fn test_targets(name: &str, function_tags: &HashMap<String, Vec<String>>) -> Vec<String> {
+    let mut targets = tag_targets(name);
+    targets.extend(HASH_REGEX.captures_iter(name).map(|x| format!("@{}", &x[1])));
+    // e.g. "tests::parses_lists" or "ParserTest.parsesLists()"
+    let function = name.split('(').next().unwrap_or(name).rsplit([':', '.', '/', ' ']).next().unwrap_or(name);
+    targets.extend(function_tags.get(function).into_iter().flatten().cloned());
+    return targets;
+}
+
+/**
+ * Mark requirements done if all of their tests passed, or failed if any failed. Ignored tests, and
+ * requirements without tests, are left alone. Returns the status changes, and a message for each tag
+ * that doesn't match a requirement.
+ */
+pub fn apply_test_results(reqs: &mut [Requirement], results: &[TestResult], function_tags: &HashMap<String, Vec<String>>, now: DateTime<FixedOffset>) -> (Vec<StatusChange>, Vec<String>) {
+    let mut outcomes: HashMap<String, TestOutcome> = HashMap::new();
+    let mut unknown: Vec<String> = Vec::new();
+    for result in results.iter().filter(|x| x.outcome != TestOutcome::Ignored) {
+        for target in test_targets(&result.name, function_tags) {
+            let found = match target.strip_prefix('@') {
+                Some(hash) => reqs.iter().find(|x| x.hash == hash).ok_or(format!("No requirement has hash {hash}.")),
+                None => lookup_requirement(reqs, &target),
+            };
+            match found {
+                Ok(req) => {
+                    let outcome = outcomes.entry(req.hash.clone()).or_insert(TestOutcome::Passed);
+                    if result.outcome == TestOutcome::Failed {
+                        *outcome = TestOutcome::Failed;
+                    }
+                },
+                Err(reason) => unknown.push(format!("Test {} is tagged with {target}. {reason}", result.name)),
+            }
+        }
+    }
+
+    let mut changes: Vec<StatusChange> = Vec::new();
+    for req in reqs.iter_mut() {
+        let status = match outcomes.get(&req.hash) {
+            Some(TestOutcome::Passed) => 1,
+            Some(TestOutcome::Failed) => FAILED_STATUS,
+            _ => continue,
+        };
+        if req.status != status {
+            changes.push(StatusChange::new(&req.hash, Some(req.status), status, now, StatusSource::Tests));
+            req.set_status(status);
+        }
+    }
+    return (changes, unknown);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::requirements::ListItem;
+
+    #[test]
+    fn parse_results() {
+        let junit = r#"<?xml version="1.0"?>
+<testsuites><testsuite name="ody" tests="3">
+    <testcase classname="ody" name="parses &quot;lists&quot;"/>
+    <testcase classname="ody" name='merge'><failure message="assertion failed">left != right</failure></testcase>
+    <testcase classname="ody" name="slow"><skipped/></testcase>
+</testsuite></testsuites>"#;
+        assert_eq!(parse_junit(junit).unwrap(), vec![
+            TestResult { name: "parses \"lists\"".to_string(), outcome: TestOutcome::Passed },
+            TestResult { name: "merge".to_string(), outcome: TestOutcome::Failed },
+            TestResult { name: "slow".to_string(), outcome: TestOutcome::Ignored },
+        ]);
+        assert!(parse_junit("<html></html>").is_none());
+
+        let json = r#"{ "type": "suite", "event": "started", "test_count": 2 }
+{ "type": "test", "event": "started", "name": "tests::parse" }
+   Compiling odysseus v0.1.0
+{ "type": "test", "name": "tests::parse", "event": "ok" }
+{ "type": "test", "name": "tests::merge", "event": "failed", "stdout": "panicked" }
+{ "type": "suite", "event": "failed", "passed": 1, "failed": 1 }"#;
+        assert_eq!(parse_cargo_json(json).unwrap(), vec![
+            TestResult { name: "tests::parse".to_string(), outcome: TestOutcome::Passed },
+            TestResult { name: "tests::merge".to_string(), outcome: TestOutcome::Failed },
+        ]);
+        assert!(parse_cargo_json("{ not json").is_none());
+    }
+    #[test]
+    fn apply_results() {
+        let mut reqs = vec![Requirement::test("111", 0).in_category("PROJ").with_id(&[1]).with_list_item(ListItem::Todo(' ')), Requirement::test("222", 1).in_category("PROJ").with_id(&[2]).with_list_item(ListItem::Todo(' ')), Requirement::test("333", 0).in_category("PROJ").with_id(&[3]).with_list_item(ListItem::Todo(' ')), Requirement::test("444", FAILED_STATUS).in_category("PROJ").with_id(&[4]).with_list_item(ListItem::Todo(' '))];
+        let function_tags = HashMap::from([("merges_sheets".to_string(), vec!["PROJ.2".to_string(), "@999".to_string()])]);
+        let result = |name: &str, outcome| TestResult { name: name.to_string(), outcome };
+        let results = vec![
+            result("tests::req_111_parses", TestOutcome::Passed),
+            result("tests::merges_sheets", TestOutcome::Failed),
+            result("tests::req_333_slow", TestOutcome::Ignored),
+            result("Requirement REQ(@444)", TestOutcome::Passed),
+            result("other::req_111_round_trip", TestOutcome::Passed),
+        ];
+        let now = DateTime::parse_from_rfc3339("2026-10-18T09:00:00+02:00").unwrap();
+        let (changes, unknown) = apply_test_results(&mut reqs, &results, &function_tags, now);
+
+        let statuses: Vec<u8> = reqs.iter().map(|x| x.status).collect();
+        assert_eq!(statuses, vec![1, FAILED_STATUS, 0, 1]);
+        assert_eq!(reqs[1].to_text_format(), "- [!] (@222)");
+        assert_eq!(changes, vec![
+            StatusChange::new("111", Some(0), 1, now, StatusSource::Tests),
+            StatusChange::new("222", Some(1), FAILED_STATUS, now, StatusSource::Tests),
+            StatusChange::new("444", Some(FAILED_STATUS), 1, now, StatusSource::Tests),
+        ]);
+        assert_eq!(unknown, vec!["Test tests::merges_sheets is tagged with @999. No requirement has hash 999.".to_string()]);
+    }
+}
@@ -0,0 +1,205 @@
+use std::{collections::HashMap, fs::read_to_string, path::PathBuf, rc::Rc};
+use mythos_core::{printerror, printinfo};
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+use super::{ListItem, Requirement, RequirementBuilder};
+
+/**
+ * Requirements can be kept in a toml document instead of a list file:
+ *
+ * [REQT]
+ * name = "Requirements Translator (REQT)"
+ *
+ * [[REQT.requirements]]
+ * hash = "7306480498125329633"
+ * id = "1"
+ * status = 0
+ * objective = "0.1.0"
+ * contents = "Allow for status updates inside reqs text files."
+ *
+ * Only id and contents are required. If hash is missing, one is generated from contents.
+ */
+#[derive(Debug, Serialize, Deserialize)]
+struct TomlCategory {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default)]
+    requirements: Vec<TomlRequirement>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct TomlRequirement {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    id: String,
+    #[serde(default)]
+    status: u8,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    objective: Option<String>,
+    contents: String,
+}
+
+pub fn is_toml(path: &PathBuf) -> bool {
+    return path.extension().is_some_and(|x| x == "toml");
+}
+
+pub fn parse_toml_requirements(path: &PathBuf, be_verbose: bool) -> Option<(Vec<Requirement>, HashMap<String, String>)> {
+    let contents = match read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) => {
+            printerror!("Could not open requirements file. {err}");
+            return None;
+        }
+    };
+    printinfo!(be_verbose, "Reading {path:?}");
+    return parse_toml_requirements_str(&contents, be_verbose);
+}
+
+pub fn parse_toml_requirements_str(contents: &str, be_verbose: bool) -> Option<(Vec<Requirement>, HashMap<String, String>)> {
+    let table: Table = match contents.parse() {
+        Ok(table) => table,
+        Err(err) => {
+            printerror!("Could not parse toml requirements. {err}");
+            return None;
+        }
+    };
+    let mut builder = RequirementBuilder::new();
+    let mut output: Vec<Requirement> = Vec::new();
+
+    for (key, value) in table {
+        let category: TomlCategory = match value.try_into() {
+            Ok(category) => category,
+            Err(err) => {
+                printerror!("Could not parse category \"{key}\". {err}");
+                return None;
+            }
+        };
+        let name = category.name.unwrap_or(key.clone());
+        let key = Rc::new(key);
+        builder.add_new_category(key.clone(), &name);
+        printinfo!(be_verbose, "\nAdded new category. Full header: {name}, Abbr: {key}");
+
+        for item in category.requirements {
+            let id: Vec<usize> = match item.id.split(".").map(|x| x.trim().parse::<usize>()).collect() {
+                Ok(id) => id,
+                Err(_) => {
+                    printerror!("Could not parse id \"{}\" in category \"{key}\".", item.id);
+                    return None;
+                }
+            };
+            let list_item = ListItem::Ordered(*id.last().unwrap_or(&0));
+
+            let mut req = match item.hash {
+                Some(hash) => Requirement {
+                    category: key.clone(),
+                    hash,
+                    id,
+                    contents: item.contents,
+                    list_item,
+                    status: 0,
+                    objective: None,
+                    created_at: None,
+                    completed_at: None,
+                    trace: None,
+                },
+                None => builder.build(item.contents, id, key.clone(), list_item),
+            };
+            req.set_status(item.status);
+            req.objective = item.objective;
+            printinfo!(be_verbose, "Read: {}", req.to_csv_format().trim_end());
+            output.push(req);
+        }
+    }
+    return Some((output, builder.2));
+}
+
+/// Write requirements as toml, grouped by category in the order they appear.
+pub fn to_toml_format(reqs: &[Requirement], categories: &HashMap<String, String>) -> Option<String> {
+    let mut grouped: Vec<(String, TomlCategory)> = Vec::new();
+    for req in reqs {
+        let item = TomlRequirement {
+            hash: Some(req.hash.clone()),
+            id: req.id_to_string(),
+            status: req.status,
+            objective: req.objective.clone(),
+            contents: req.contents.clone(),
+        };
+        match grouped.iter_mut().find(|(key, _)| *key == *req.category) {
+            Some((_, category)) => category.requirements.push(item),
+            None => grouped.push((req.category.to_string(), TomlCategory {
+                name: categories.get(req.category.as_str()).cloned(),
+                requirements: vec![item],
+            })),
+        }
+    }
+
+    let mut table = Table::new();
+    for (key, category) in grouped {
+        match Value::try_from(category) {
+            Ok(value) => { table.insert(key, value); },
+            Err(err) => {
+                printerror!("Could not convert requirements to toml. {err}");
+                return None;
+            }
+        }
+    }
+    return match toml::to_string(&table) {
+        Ok(output) => Some(output),
+        Err(err) => {
+            printerror!("Could not convert requirements to toml. {err}");
+            None
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML: &str = r#"
+[REQT]
+name = "Requirements Translator (REQT)"
+
+[[REQT.requirements]]
+hash = "h1"
+id = "1"
+contents = "First."
+
+[[REQT.requirements]]
+hash = "h2"
+id = "1.1"
+status = 1
+objective = "0.1.0"
+contents = "Second."
+
+[ADMN]
+requirements = [{ id = "1", contents = "No hash." }]
+"#;
+
+    #[test]
+    fn parse_toml() {
+        let (reqs, categories) = parse_toml_requirements_str(TOML, true).unwrap();
+        assert_eq!(reqs.len(), 3);
+        assert_eq!(categories["REQT"], "Requirements Translator (REQT)".to_string());
+        assert_eq!(categories["ADMN"], "ADMN".to_string());
+
+        assert_eq!(reqs[1].to_text_format(), "\t1. [x] Second.(@h2)");
+        assert_eq!(reqs[1].objective, Some("0.1.0".to_string()));
+        assert_eq!(*reqs[2].category, "ADMN".to_string());
+        assert!(!reqs[2].hash.is_empty());
+    }
+    #[test]
+    fn toml_round_trip() {
+        let (reqs, categories) = parse_toml_requirements_str(TOML, true).unwrap();
+        let output = to_toml_format(&reqs, &categories).unwrap();
+        // Category order is preserved.
+        assert!(output.find("[REQT]").unwrap() < output.find("[ADMN]").unwrap());
+
+        let (output, _) = parse_toml_requirements_str(&output, true).unwrap();
+        for (a, b) in reqs.iter().zip(output.iter()) {
+            assert_eq!(a.to_csv_format(), b.to_csv_format());
+            assert_eq!(a.objective, b.objective);
+        }
+    }
+}
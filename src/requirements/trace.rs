@@ -0,0 +1,332 @@
+use std::{collections::HashMap, fs, path::{Path, PathBuf}, sync::LazyLock};
+use mythos_core::printerror;
+use regex::Regex;
+use serde::Serialize;
+
+use super::{lookup_requirement, Requirement};
+
+const TRACE_HEADER: &str = "Hash,Category,Id,Contents,Implementations,Tests\n";
+/// Directories that are never scanned, besides hidden ones.
+const SKIPPED_DIRS: [&str; 2] = ["target", "node_modules"];
+
+static TAG_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"REQ(?:\(@(?<hash>[^)\s]+)\)|:\s*(?<id>[\w-]+(?:\.\w+)*))").unwrap());
+static FUNCTION_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\b(?:fn|def|function|func|void)\s+(\w+)").unwrap());
+/// e.g. #[test], #[tokio::test] or @Test
+static TEST_ATTRIBUTE_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(?:#\[(?:\w+::)*test\]|@Test\b)").unwrap());
+
+/// A `REQ(@hash)` or `REQ: PROJ.1.2` tag found in a source file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraceTag {
+    /// "@hash" or an id, as written in the tag.
+    pub target: String,
+    /// "path:line"
+    pub location: String,
+    pub is_test: bool,
+}
+
+/// A tag that doesn't resolve to a requirement, and why.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UnknownTag {
+    #[serde(flatten)]
+    pub tag: TraceTag,
+    pub reason: String,
+}
+
+/// Where one requirement is referenced.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TraceEntry {
+    pub hash: String,
+    pub category: String,
+    pub id: String,
+    pub contents: String,
+    pub implementations: Vec<String>,
+    pub tests: Vec<String>,
+}
+
+/// Requirement -> locations matrix, in the order of the requirements.
+#[derive(Debug, Serialize)]
+pub struct Trace {
+    pub requirements: Vec<TraceEntry>,
+    pub unknown: Vec<UnknownTag>,
+}
+
+/**
+ * Find every tag in the files under $dir. Hidden directories, target and node_modules are skipped,
+ * as are files that aren't utf-8 text. Files are read in name order, so the output is stable.
+ */
+pub fn scan_sources(dir: &Path) -> Option<Vec<TraceTag>> {
+    let mut tags: Vec<TraceTag> = Vec::new();
+    for (path, contents) in source_files(dir)? {
+        tags.extend(scan_file(&path, &contents));
+    }
+    return Some(tags);
+}
+
+/// Paths and contents of the files scan_sources reads.
+fn source_files(dir: &Path) -> Option<Vec<(PathBuf, String)>> {
+    let mut entries: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(|x| x.ok()).map(|x| x.path()).collect(),
+        Err(err) => {
+            printerror!("Could not read directory {dir:?}. {err}");
+            return None;
+        }
+    };
+    entries.sort();
+
+    let mut files: Vec<(PathBuf, String)> = Vec::new();
+    for path in entries {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        if path.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                files.extend(source_files(&path)?);
+            }
+        } else if let Ok(contents) = fs::read_to_string(&path) {
+            files.push((path, contents));
+        }
+    }
+    return Some(files);
+}
+
+/// Tags in one file. Tags after `#[cfg(test)]` count as tests, like those in test files.
+pub fn scan_file(path: &Path, contents: &str) -> Vec<TraceTag> {
+    let mut is_test = is_test_path(path);
+    let mut tags: Vec<TraceTag> = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        is_test |= line.trim() == "#[cfg(test)]";
+        for target in tag_targets(line) {
+            tags.push(TraceTag { target, location: format!("{}:{}", path.display(), i + 1), is_test });
+        }
+    }
+    return tags;
+}
+
+/// "@hash" or the id of each tag in $text.
+pub fn tag_targets(text: &str) -> Vec<String> {
+    return TAG_REGEX.captures_iter(text).filter_map(|captures| match (captures.name("hash"), captures.name("id")) {
+        (Some(hash), _) => Some(format!("@{}", hash.as_str())),
+        (None, Some(id)) => Some(id.as_str().to_string()),
+        (None, None) => None,
+    }).collect();
+}
+
+/**
+ * Tags in the comments and attributes/decorators directly above each test function under $dir, by function
+ * name. This is how tests are tagged in their doc comments. Tests with the same name share their tags.
+ */
+pub fn scan_function_tags(dir: &Path) -> Option<HashMap<String, Vec<String>>> {
+    let mut output: HashMap<String, Vec<String>> = HashMap::new();
+    for (path, contents) in source_files(dir)? {
+        for (function, mut targets) in function_tags(&path, &contents) {
+            output.entry(function).or_default().append(&mut targets);
+        }
+    }
+    return Some(output);
+}
+
+/**
+ * Tagged test functions of one file, with their tags. Blank lines and code between the comments and the function
+ * break the link. Functions are tests if they have a test attribute, are named test..., or are in a test file or
+ * after `#[cfg(test)]`, like the tags of scan_file.
+ */
+fn function_tags(path: &Path, contents: &str) -> Vec<(String, Vec<String>)> {
+    let mut is_test_file = is_test_path(path);
+    let mut output: Vec<(String, Vec<String>)> = Vec::new();
+    let mut pending: Vec<String> = Vec::new();
+    let mut has_test_attribute = false;
+    for line in contents.lines().map(|x| x.trim()) {
+        is_test_file |= line == "#[cfg(test)]";
+        if ["//", "/*", "*", "#", "@"].iter().any(|x| line.starts_with(x)) {
+            pending.extend(tag_targets(line));
+            has_test_attribute |= TEST_ATTRIBUTE_REGEX.is_match(line);
+            continue;
+        }
+        if let Some(captures) = FUNCTION_REGEX.captures(line).filter(|_| !pending.is_empty()) {
+            if is_test_file || has_test_attribute || captures[1].starts_with("test") {
+                output.push((captures[1].to_string(), std::mem::take(&mut pending)));
+            }
+        }
+        pending.clear();
+        has_test_attribute = false;
+    }
+    return output;
+}
+
+/// In a test/tests/spec directory, or named like a test, e.g. test_parser.py, parser_test.go or parser.test.ts.
+fn is_test_path(path: &Path) -> bool {
+    let is_test_dir = path.parent().is_some_and(|dir| dir.components().any(|x| matches!(x.as_os_str().to_str(), Some("test" | "tests" | "spec"))));
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    return is_test_dir
+        || stem.starts_with("test_")
+        || ["_test", ".test", "_spec", ".spec", "_tests"].iter().any(|x| stem.ends_with(x));
+}
+
+impl TraceEntry {
+    fn new(req: &Requirement) -> TraceEntry {
+        return TraceEntry {
+            hash: req.hash.clone(),
+            category: req.category.to_string(),
+            id: req.id_to_string(),
+            contents: req.contents.clone(),
+            implementations: Vec::new(),
+            tests: Vec::new(),
+        };
+    }
+
+    /// Value of the spreadsheet's Trace column. None if the requirement isn't referenced.
+    pub fn to_column(&self) -> Option<String> {
+        let locations: Vec<&str> = self.implementations.iter().chain(&self.tests).map(|x| x.as_str()).collect();
+        return if locations.is_empty() { None } else { Some(locations.join("; ")) };
+    }
+
+    fn to_label(&self) -> String {
+        return format!("{} {} {}(@{})", self.category, self.id, self.contents, self.hash);
+    }
+}
+
+impl Trace {
+    /// Hash tags only match hashes. Id tags are resolved like ody set-status ids, so they need a category if the id is ambiguous.
+    pub fn new(reqs: &[Requirement], tags: Vec<TraceTag>) -> Trace {
+        let mut trace = Trace { requirements: reqs.iter().map(TraceEntry::new).collect(), unknown: Vec::new() };
+        for tag in tags {
+            let found = match tag.target.strip_prefix('@') {
+                Some(hash) => reqs.iter().find(|x| x.hash == hash).ok_or(format!("No requirement has hash {hash}.")),
+                None => lookup_requirement(reqs, &tag.target),
+            };
+            let entry = found.map(|req| trace.requirements.iter_mut().find(|x| x.hash == req.hash).unwrap());
+            match entry {
+                Ok(entry) if tag.is_test => entry.tests.push(tag.location),
+                Ok(entry) => entry.implementations.push(tag.location),
+                Err(reason) => trace.unknown.push(UnknownTag { tag, reason }),
+            }
+        }
+        return trace;
+    }
+
+    pub fn unimplemented(&self) -> impl Iterator<Item = &TraceEntry> {
+        return self.requirements.iter().filter(|x| x.implementations.is_empty());
+    }
+
+    pub fn untested(&self) -> impl Iterator<Item = &TraceEntry> {
+        return self.requirements.iter().filter(|x| x.tests.is_empty());
+    }
+
+    /// The matrix, followed by requirements missing implementations or tests, and unknown tags.
+    pub fn to_text_format(&self) -> String {
+        let mut output = String::new();
+        for entry in &self.requirements {
+            output += &format!("{}\n", entry.to_label());
+            if entry.implementations.is_empty() && entry.tests.is_empty() {
+                output += "    not referenced\n";
+            }
+            if !entry.implementations.is_empty() {
+                output += &format!("    implemented: {}\n", entry.implementations.join(", "));
+            }
+            if !entry.tests.is_empty() {
+                output += &format!("    tested:      {}\n", entry.tests.join(", "));
+            }
+        }
+
+        let unimplemented: Vec<&TraceEntry> = self.unimplemented().collect();
+        let untested: Vec<&TraceEntry> = self.untested().collect();
+        for (title, entries) in [("No implementation", &unimplemented), ("No tests", &untested)] {
+            if !entries.is_empty() {
+                output += &format!("\n{title} ({}):\n", entries.len());
+                for entry in entries {
+                    output += &format!("    {}\n", entry.to_label());
+                }
+            }
+        }
+        if !self.unknown.is_empty() {
+            output += &format!("\nUnknown tags ({}):\n", self.unknown.len());
+            for unknown in &self.unknown {
+                output += &format!("    {} {}: {}\n", unknown.tag.location, unknown.tag.target, unknown.reason);
+            }
+        }
+        output += &format!("\n{} requirement(s), {} implemented, {} tested, {} unknown tag(s).\n",
+            self.requirements.len(),
+            self.requirements.len() - unimplemented.len(),
+            self.requirements.len() - untested.len(),
+            self.unknown.len());
+        return output;
+    }
+
+    /// One row per requirement. Locations are separated by "; ".
+    pub fn to_csv_format(&self) -> String {
+        let mut output = TRACE_HEADER.to_string();
+        for entry in &self.requirements {
+            output += &format!("{},{},{},{},{},{}\n", entry.hash, entry.category, entry.id, entry.contents, entry.implementations.join("; "), entry.tests.join("; "));
+        }
+        return output;
+    }
+
+    pub fn to_json_format(&self) -> Option<String> {
+        return match serde_json::to_string_pretty(self) {
+            Ok(output) => Some(output + "\n"),
+            Err(err) => {
+                printerror!("Could not convert trace to json. {err}");
+                None
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_tags() {
+        let contents = "// REQ(@123)\nfn parse() {} // REQ: PROJ.1.2, REQ: DOC.1\n\n#[cfg(test)]\nmod tests {\n    // REQ(@123)\n}\n";
+        let tags = scan_file(Path::new("src/parser.rs"), contents);
+        let tags: Vec<(&str, &str, bool)> = tags.iter().map(|x| (x.target.as_str(), x.location.as_str(), x.is_test)).collect();
+        assert_eq!(tags, vec![
+            ("@123", "src/parser.rs:1", false),
+            ("PROJ.1.2", "src/parser.rs:2", false),
+            ("DOC.1", "src/parser.rs:2", false),
+            ("@123", "src/parser.rs:6", true),
+        ]);
+        assert_eq!(tag_targets("Requirement REQ(@123) parses, REQ: PROJ.1"), vec!["@123", "PROJ.1"]);
+        assert!(is_test_path(Path::new("tests/cli.rs")));
+        assert!(is_test_path(Path::new("web/parser.test.ts")));
+        assert!(is_test_path(Path::new("test_parser.py")));
+        assert!(!is_test_path(Path::new("src/contest.rs")));
+    }
+    #[test]
+    fn tagged_functions() {
+        let contents = "/// Parses lists. REQ(@123)\n#[test]\nfn parses_lists() {}\n\n// REQ: PROJ.2\n\nfn untagged() {}\n    # REQ: PROJ.3\n    @pytest.mark.slow\n    def test_slow(self):\n\
+            /// Not a test. REQ: PROJ.4\npub fn parse() {}\n\n#[cfg(test)]\nmod tests {\n    /// REQ: PROJ.5\n    fn parse() {}\n}\n";
+        assert_eq!(function_tags(Path::new("src/lib.rs"), contents), vec![
+            ("parses_lists".to_string(), vec!["@123".to_string()]),
+            ("test_slow".to_string(), vec!["PROJ.3".to_string()]),
+            ("parse".to_string(), vec!["PROJ.5".to_string()]),
+        ]);
+        // Every function of a test file is a test.
+        assert_eq!(function_tags(Path::new("tests/cli.rs"), "// REQ: PROJ.6
+fn helper() {}
+"), vec![("helper".to_string(), vec!["PROJ.6".to_string()])]);
+    }
+    #[test]
+    fn trace_matrix() {
+        let reqs = vec![Requirement::test("h1", 0).in_category("PROJ").with_id(&[1]).with_contents("Requirement h1."), Requirement::test("h2", 0).in_category("PROJ").with_id(&[1, 2]).with_contents("Requirement h2."), Requirement::test("h3", 0).in_category("DOC").with_id(&[1]).with_contents("Requirement h3."), Requirement::test("h4", 0).in_category("TEST").with_id(&[1]).with_contents("Requirement h4.")];
+        let tag = |target: &str, location: &str, is_test: bool| TraceTag { target: target.to_string(), location: location.to_string(), is_test };
+        let tags = vec![
+            tag("@h1", "src/a.rs:1", false),
+            tag("PROJ.1.2", "src/a.rs:2", false),
+            tag("@h1", "tests/a.rs:3", true),
+            tag("@missing", "src/b.rs:4", false),
+            tag("1", "src/b.rs:5", false),
+        ];
+        let trace = Trace::new(&reqs, tags);
+        assert_eq!(trace.requirements[0].implementations, vec!["src/a.rs:1"]);
+        assert_eq!(trace.requirements[0].tests, vec!["tests/a.rs:3"]);
+        assert_eq!(trace.requirements[0].to_column(), Some("src/a.rs:1; tests/a.rs:3".to_string()));
+        assert_eq!(trace.requirements[1].implementations, vec!["src/a.rs:2"]);
+        assert_eq!(trace.requirements[2].to_column(), None);
+        assert_eq!(trace.unimplemented().map(|x| x.hash.as_str()).collect::<Vec<&str>>(), vec!["h3", "h4"]);
+        assert_eq!(trace.untested().count(), 3);
+        // Hash tags don't fall back to ids, and ambiguous ids are unknown.
+        assert_eq!(trace.unknown.iter().map(|x| x.tag.location.as_str()).collect::<Vec<&str>>(), vec!["src/b.rs:4", "src/b.rs:5"]);
+        assert!(trace.unknown[1].reason.contains("several categories"));
+        assert_eq!(trace.to_csv_format().lines().nth(1), Some("h1,PROJ,1,Requirement h1.,src/a.rs:1,tests/a.rs:3"));
+    }
+}
@@ -0,0 +1,234 @@
+use std::{collections::HashMap, path::PathBuf};
+use calamine::{open_workbook_auto, Reader};
+use mythos_core::{printerror, printinfo};
+use rust_xlsxwriter::{DataValidation, DataValidationRule, Format, Workbook, Worksheet};
+use spreadsheet_ods::{condition::Condition, validation::Validation, Sheet, WorkBook};
+
+use super::{history::format_timestamp, parse_spreadsheet_row, Requirement};
+
+pub const HEADER: [&str; 9] = ["Hash", "Category", "Id", "Contents", "Status", "Objective", "Created", "Completed", "Trace"];
+const STATUS_COL: u16 = 4;
+const SINGLE_SHEET_NAME: &str = "Requirements";
+
+/// Whether path should be read/written as a native spreadsheet, rather than csv/md.
+pub fn is_workbook(path: &PathBuf) -> bool {
+    return matches!(path.extension().and_then(|x| x.to_str()), Some("xlsx") | Some("ods"));
+}
+
+/// Read every sheet of an .xlsx/.ods file. Rows are read the same way as csv lines, so sheets
+/// can be split up however the user likes, as long as each begins with a header row.
+pub fn parse_workbook(path: &PathBuf, be_verbose: bool) -> Option<HashMap<String, Requirement>> {
+    let mut workbook = match open_workbook_auto(path) {
+        Ok(workbook) => workbook,
+        Err(err) => {
+            printerror!("Could not open spreadsheet. {err}");
+            return None;
+        }
+    };
+    let mut output: HashMap<String, Requirement> = HashMap::new();
+
+    printinfo!(be_verbose, "\nReading {path:?}");
+    for name in workbook.sheet_names() {
+        let range = match workbook.worksheet_range(&name) {
+            Ok(range) => range,
+            Err(err) => {
+                printerror!("Error reading sheet \"{name}\". {err}");
+                return None;
+            }
+        };
+        printinfo!(be_verbose, "Reading sheet \"{name}\".");
+
+        // Skip header.
+        for (i, row) in range.rows().enumerate().skip(1) {
+            let values: Vec<String> = row.iter().map(|x| x.to_string().trim().to_string()).collect();
+            if values.iter().all(|x| x.is_empty()) { continue; }
+            let values: Vec<&str> = values.iter().map(|x| x.as_str()).collect();
+            parse_spreadsheet_row(&values, i, &mut output)?;
+        }
+    }
+    return Some(output);
+}
+
+/// Whether an existing workbook was written with one sheet per category.
+pub fn has_sheet_per_category(path: &PathBuf) -> bool {
+    return open_workbook_auto(path).is_ok_and(|x| x.sheet_names().len() > 1);
+}
+
+/// Write requirements to an .xlsx/.ods file, either on a single sheet or one sheet per category.
+/// Header rows are frozen and the Status column only accepts values 0-255.
+pub fn write_workbook(path: &PathBuf, reqs: &[Requirement], use_sheet_per_category: bool) -> Option<()> {
+    // Keep categories in the order they appear.
+    let mut sheets: Vec<(String, Vec<&Requirement>)> = Vec::new();
+    for req in reqs {
+        let name = if use_sheet_per_category {
+            sheet_name(&req.category)
+        } else {
+            SINGLE_SHEET_NAME.to_string()
+        };
+        match sheets.iter_mut().find(|(x, _)| *x == name) {
+            Some((_, rows)) => rows.push(req),
+            None => sheets.push((name, vec![req])),
+        }
+    }
+    if sheets.is_empty() {
+        sheets.push((SINGLE_SHEET_NAME.to_string(), Vec::new()));
+    }
+
+    let res = if path.extension().is_some_and(|x| x == "ods") {
+        write_ods(path, &sheets).map_err(|x| x.to_string())
+    } else {
+        write_xlsx(path, &sheets).map_err(|x| x.to_string())
+    };
+    if let Err(err) = res {
+        printerror!("Error while writing spreadsheet. {err}");
+        return None;
+    }
+    return Some(());
+}
+
+fn write_xlsx(path: &PathBuf, sheets: &[(String, Vec<&Requirement>)]) -> Result<(), rust_xlsxwriter::XlsxError> {
+    let mut workbook = Workbook::new();
+    let bold = Format::new().set_bold();
+    let validation = DataValidation::new()
+        .allow_whole_number(DataValidationRule::Between(0, 255))
+        .set_error_message("Status must be a whole number from 0 to 255.")?;
+
+    for (name, reqs) in sheets {
+        let sheet: &mut Worksheet = workbook.add_worksheet();
+        sheet.set_name(name)?;
+        for (col, title) in HEADER.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, *title, &bold)?;
+        }
+        for (i, req) in reqs.iter().enumerate() {
+            let row = i as u32 + 1;
+            // Write ids as strings, so 1.10 is not read back as 1.1.
+            sheet.write_string(row, 0, &req.hash)?;
+            sheet.write_string(row, 1, req.category.as_str())?;
+            sheet.write_string(row, 2, req.id_to_string())?;
+            sheet.write_string(row, 3, &req.contents)?;
+            sheet.write_number(row, STATUS_COL, req.status)?;
+            sheet.write_string(row, STATUS_COL + 1, req.objective.as_deref().unwrap_or(""))?;
+            sheet.write_string(row, STATUS_COL + 2, format_timestamp(req.created_at.as_ref()))?;
+            sheet.write_string(row, STATUS_COL + 3, format_timestamp(req.completed_at.as_ref()))?;
+            sheet.write_string(row, STATUS_COL + 4, req.trace.as_deref().unwrap_or(""))?;
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.add_data_validation(1, STATUS_COL, u32::max(reqs.len() as u32, 1), STATUS_COL, &validation)?;
+        sheet.autofit();
+    }
+    return workbook.save(path);
+}
+
+fn write_ods(path: &PathBuf, sheets: &[(String, Vec<&Requirement>)]) -> Result<(), spreadsheet_ods::OdsError> {
+    let mut workbook = WorkBook::default();
+    let mut validation = Validation::new();
+    validation.set_condition(Condition::content_is_whole_number_and(Condition::content_le(255)));
+    let validation = workbook.add_validation(validation);
+
+    for (name, reqs) in sheets {
+        let mut sheet = Sheet::new(name);
+        for (col, title) in HEADER.iter().enumerate() {
+            sheet.set_value(0, col as u32, *title);
+        }
+        for (i, req) in reqs.iter().enumerate() {
+            let row = i as u32 + 1;
+            sheet.set_value(row, 0, req.hash.as_str());
+            sheet.set_value(row, 1, req.category.as_str());
+            sheet.set_value(row, 2, req.id_to_string());
+            sheet.set_value(row, 3, req.contents.as_str());
+            sheet.set_value(row, STATUS_COL as u32, req.status);
+            sheet.set_validation(row, STATUS_COL as u32, &validation);
+            sheet.set_value(row, STATUS_COL as u32 + 1, req.objective.as_deref().unwrap_or(""));
+            sheet.set_value(row, STATUS_COL as u32 + 2, format_timestamp(req.created_at.as_ref()));
+            sheet.set_value(row, STATUS_COL as u32 + 3, format_timestamp(req.completed_at.as_ref()));
+            sheet.set_value(row, STATUS_COL as u32 + 4, req.trace.as_deref().unwrap_or(""));
+        }
+        sheet.split_row_header(0);
+        workbook.push_sheet(sheet);
+    }
+    return spreadsheet_ods::write_ods(&mut workbook, path);
+}
+
+/// Sheet names are limited to 31 chars and cannot contain []:*?/\
+fn sheet_name(category: &str) -> String {
+    let name: String = category.chars()
+        .map(|x| if "[]:*?/\\".contains(x) { '_' } else { x })
+        .take(31)
+        .collect();
+    if name.is_empty() {
+        return SINGLE_SHEET_NAME.to_string();
+    }
+    return name;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+    use crate::requirements::ListItem;
+
+    fn reqs() -> Vec<Requirement> {
+        return vec![
+            Requirement {
+                category: Rc::new("CAT".to_string()),
+                hash: "7306480498125329633".to_string(),
+                id: vec![1],
+                contents: "First, with a comma.".to_string(),
+                list_item: ListItem::Ordered(1),
+                status: 0,
+                objective: None,
+                created_at: None,
+                completed_at: None,
+                trace: None,
+            },
+            Requirement {
+                category: Rc::new("DOG".to_string()),
+                hash: "h2".to_string(),
+                id: vec![1, 10],
+                contents: "Second.".to_string(),
+                list_item: ListItem::Hybrid(10, 'x'),
+                status: 1,
+                objective: Some("1.0.0".to_string()),
+                created_at: None,
+                completed_at: chrono::DateTime::parse_from_rfc3339("2026-10-18T12:30:00+02:00").ok(),
+                trace: Some("src/main.rs:12; tests/cli.rs:40".to_string()),
+            },
+        ];
+    }
+
+    fn round_trip(file_name: &str, use_sheet_per_category: bool) {
+        let path = std::env::temp_dir().join(file_name);
+        write_workbook(&path, &reqs(), use_sheet_per_category).unwrap();
+        let output = parse_workbook(&path, true).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(output.len(), 2);
+        let req = &output["7306480498125329633"];
+        assert_eq!(*req.category, "CAT".to_string());
+        assert_eq!(req.contents, "First, with a comma.".to_string());
+        assert_eq!(req.objective, None);
+        let req = &output["h2"];
+        assert_eq!(req.id_to_string(), "1.10".to_string());
+        assert_eq!(req.status, 1);
+        assert_eq!(req.objective, Some("1.0.0".to_string()));
+        assert_eq!(req.completed_at, reqs()[1].completed_at);
+        assert_eq!(req.trace, reqs()[1].trace);
+    }
+
+    #[test]
+    fn xlsx_round_trip() {
+        round_trip("odysseus_test.xlsx", false);
+        round_trip("odysseus_test_categories.xlsx", true);
+    }
+    #[test]
+    fn ods_round_trip() {
+        round_trip("odysseus_test.ods", false);
+        round_trip("odysseus_test_categories.ods", true);
+    }
+    #[test]
+    fn clean_sheet_names() {
+        assert_eq!(sheet_name("A/B"), "A_B".to_string());
+        assert_eq!(sheet_name(&"A".repeat(40)).len(), 31);
+    }
+}